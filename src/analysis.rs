@@ -0,0 +1,129 @@
+//! Provides a function for analysing every move available from a position.
+
+use std::cmp::{Ordering, Reverse};
+
+use crate::game::Game;
+use crate::moves::Move;
+use crate::solver;
+use crate::status::Status;
+
+/// Represents the solved outcome of a single move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MoveAnalysis {
+    /// The move analysed.
+    pub player_move: Move,
+
+    /// The victory status after the move, from the perspective of the player
+    /// making the move.
+    pub status: Status,
+
+    /// The final margin after the move with both players playing to maximise
+    /// their own margin, from the perspective of the player making the move.
+    pub margin: i8,
+}
+
+/// Solves every move available from a specified game position, returning the
+/// outcome of each move ordered from best to worst.
+///
+/// Unlike `solver::solve`, which returns as soon as it finds a winning move,
+/// every move is solved fully, so this takes much longer than solving the
+/// position. Moves with the same margin are returned in order of preference.
+/// If the game is over, no moves are returned.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub fn analyse(game: Game, games: &mut u64) -> Vec<MoveAnalysis> {
+    let mut analyses: Vec<MoveAnalysis> = solver::get_moves(&game)
+        .into_iter()
+        .map(|player_move| {
+            let margin = -solver::solve_margin(game.after(player_move), games);
+
+            MoveAnalysis {
+                player_move,
+                status: get_status(margin),
+                margin,
+            }
+        })
+        .collect();
+
+    // The sort is stable, so moves with the same margin stay in order of
+    // preference.
+    analyses.sort_by_key(|analysis| Reverse(analysis.margin));
+    analyses
+}
+
+/// Returns the victory status corresponding to the specified final margin.
+fn get_status(margin: i8) -> Status {
+    match margin.cmp(&0) {
+        Ordering::Greater => Status::Win,
+        Ordering::Equal => Status::Draw,
+        Ordering::Less => Status::Loss,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::create_game;
+    use crate::moves::Move::*;
+    use crate::status::Status::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn test_analyse() {
+        let mut games = 0;
+
+        // Every move loses, but the opponent can only win by a single line
+        // after a Diagonal in square 10 or 11
+        assert_eq!(
+            vec![
+                MoveAnalysis {
+                    player_move: Play(Diagonal, 10),
+                    status: Loss,
+                    margin: -1
+                },
+                MoveAnalysis {
+                    player_move: Play(Diagonal, 11),
+                    status: Loss,
+                    margin: -1
+                },
+                MoveAnalysis {
+                    player_move: Play(Diagonal, 15),
+                    status: Loss,
+                    margin: -2
+                },
+                MoveAnalysis {
+                    player_move: Play(Diagonal, 14),
+                    status: Loss,
+                    margin: -2
+                },
+            ],
+            analyse(create_game(Puller, Pusher, Straight), &mut games)
+        );
+
+        // Every move is analysed, not just the first winning move
+        let analyses = analyse(create_game(Puller, Straight, Diagonal), &mut games);
+        assert_eq!(4, analyses.len());
+        assert_eq!(Play(Pusher, 10), analyses[0].player_move);
+        assert_eq!(Win, analyses[0].status);
+        assert!(analyses
+            .windows(2)
+            .all(|pair| pair[0].margin >= pair[1].margin));
+
+        assert!(analyse(
+            create_game(Puller, Straight, Diagonal)
+                .with_move(Pusher, 10)
+                .with_pass()
+                .with_move(Pusher, 15)
+                .with_pass(),
+            &mut games
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_get_status() {
+        assert_eq!(Win, get_status(2));
+        assert_eq!(Draw, get_status(0));
+        assert_eq!(Loss, get_status(-1));
+    }
+}
@@ -0,0 +1,156 @@
+//! Defines a struct describing an arrangement of the four quarters of the
+//! board.
+
+use crate::geometry::BoardGeometry;
+
+/// Describes an arrangement of the four 2×2 quarters of the board, which can be
+/// placed side by side in different shapes instead of forming the standard
+/// 4×4 square.
+///
+/// Each quarter is placed with its top left square at a row and column, which
+/// may be negative. The squares of quarter `q` are squares `4q` to `4q + 3` of
+/// the board, in this order within the quarter:
+///
+/// ```text
+/// 0 1
+/// 2 3
+/// ```
+///
+/// So the squares of `Arrangement::SQUARE` are not numbered in the same order
+/// as the standard board, although the geometry is otherwise the same.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Arrangement([(i8, i8); 4]);
+
+impl Default for Arrangement {
+    /// Returns the arrangement of the standard board.
+    fn default() -> Self {
+        Arrangement::SQUARE
+    }
+}
+
+impl Arrangement {
+    /// The quarters arranged as the standard 4×4 square.
+    pub const SQUARE: Arrangement = Arrangement([(0, 0), (0, 2), (2, 0), (2, 2)]);
+
+    /// The quarters arranged as a 2×8 strip.
+    pub const STRIP: Arrangement = Arrangement([(0, 0), (0, 2), (0, 4), (0, 6)]);
+
+    /// The quarters arranged as an L shape, three quarters high and two wide.
+    pub const L_SHAPE: Arrangement = Arrangement([(0, 0), (2, 0), (4, 0), (4, 2)]);
+
+    /// The quarters arranged as a square with the lower half offset by one
+    /// column.
+    pub const OFFSET: Arrangement = Arrangement([(0, 0), (0, 2), (2, 1), (2, 3)]);
+
+    /// Returns the arrangement with the top left square of each quarter at the
+    /// specified row and column, or `None` if any quarters overlap, or if the
+    /// bottom right square of a quarter would be beyond the range of an `i8`.
+    pub fn new(quarters: [(i8, i8); 4]) -> Option<Self> {
+        if quarters
+            .iter()
+            .any(|&(row, column)| row == i8::MAX || column == i8::MAX)
+        {
+            return None;
+        }
+
+        let arrangement = Arrangement(quarters);
+        let squares = arrangement.get_squares();
+
+        let overlaps = squares
+            .iter()
+            .enumerate()
+            .any(|(index, square)| squares[..index].contains(square));
+
+        (!overlaps).then_some(arrangement)
+    }
+
+    /// Returns the row and column of the top left square of each quarter.
+    pub fn get_quarters(&self) -> [(i8, i8); 4] {
+        self.0
+    }
+
+    /// Returns the row and column of each square of the board.
+    pub fn get_squares(&self) -> [(i8, i8); 16] {
+        let mut squares = [(0, 0); 16];
+
+        for (quarter, (row, column)) in self.0.iter().copied().enumerate() {
+            for square in 0..4 {
+                squares[quarter * 4 + square] = (row + square as i8 / 2, column + square as i8 % 2);
+            }
+        }
+
+        squares
+    }
+
+    /// Returns the geometry of the arrangement, scoring every horizontal,
+    /// vertical, and diagonal line of the specified number of consecutive
+    /// squares.
+    ///
+    /// Every square is available for the first move, but squares can be made
+    /// unavailable with `BoardGeometry::with_start_unavailable`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line length is less than 2.
+    pub fn geometry(&self, line_length: u8) -> BoardGeometry {
+        BoardGeometry::from_squares(&self.get_squares(), line_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn new() {
+        assert_eq!(
+            Some(Arrangement::OFFSET),
+            Arrangement::new([(0, 0), (0, 2), (2, 1), (2, 3)])
+        );
+
+        // The second quarter overlaps the right column of the first quarter.
+        assert_eq!(None, Arrangement::new([(0, 0), (0, 1), (2, 0), (2, 2)]));
+
+        // The squares of the first quarter would overflow.
+        assert_eq!(None, Arrangement::new([(127, 0), (0, 2), (0, 4), (0, 6)]));
+        assert!(Arrangement::new([(126, -128), (0, 2), (0, 4), (0, 6)]).is_some());
+    }
+
+    #[test]
+    fn get_squares() {
+        assert_eq!(
+            [(0, 0), (0, 1), (1, 0), (1, 1)],
+            Arrangement::STRIP.get_squares()[..4]
+        );
+        assert_eq!(
+            [(0, 6), (0, 7), (1, 6), (1, 7)],
+            Arrangement::STRIP.get_squares()[12..]
+        );
+    }
+
+    #[test]
+    fn geometry() {
+        let geometry = Arrangement::SQUARE.geometry(3);
+        assert_eq!(24, geometry.get_lines().len());
+
+        // A Straight in the top left square allows moves in the top row (squares
+        // 1, 4, and 5) and the left column (squares 2, 8, and 10).
+        assert_eq!(
+            !0b_0000_0101_0011_0110,
+            u16::from(geometry.get_unavailable(Straight, 0))
+        );
+
+        // A 2×8 strip has six lines of three squares in each row.
+        assert_eq!(12, Arrangement::STRIP.geometry(3).get_lines().len());
+
+        // The first line in the left column of the L shape is in the first
+        // two quarters.
+        let geometry = Arrangement::L_SHAPE.geometry(3);
+        assert!(geometry
+            .get_lines()
+            .contains(&Board::from(0b_0000_0000_0001_0101)));
+        assert_eq!(Board::from(u16::MAX), geometry.get_playable());
+    }
+}
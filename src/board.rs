@@ -7,7 +7,7 @@ use std::fmt::{self, Display};
 /// The board is represented as a `u16` bit field with the squares corresponding
 /// to these bits:
 ///
-/// ```
+/// ```text
 ///  0  1  2  3
 ///  4  5  6  7
 ///  8  9 10 11
@@ -57,8 +57,9 @@ impl Display for Board {
 impl Board {
     /// Bit fields representing the possible lines of three squares.
     ///
-    /// Only used in evaluating the SCORES constant at compile time.
-    const LINES: [u16; 24] = [
+    /// Used in evaluating the SCORES constant at compile time, and in checking
+    /// that a solution database was built under the same rules.
+    pub(crate) const LINES: [u16; 24] = [
         0b_0000_0000_0000_0111,
         0b_0000_0000_0000_1110,
         0b_0000_0000_0111_0000,
@@ -95,12 +96,15 @@ impl Board {
     const fn calculate_scores() -> [u8; 65536] {
         let mut scores = [0; 65536];
 
+        // Evaluated once, as evaluating it in the loop makes evaluating the
+        // function at compile time take too long.
+        let line_count = Self::LINES.len();
         let mut board = 0;
 
         loop {
             let mut line = 0;
 
-            while line < 24 {
+            while line < line_count {
                 if board & Self::LINES[line] == Self::LINES[line] {
                     scores[board as usize] += 1;
                 }
@@ -138,6 +142,55 @@ impl Board {
         Board(self.0 | (1 << square))
     }
 
+    /// Returns an instance transformed by one of the eight symmetries of the
+    /// board.
+    ///
+    /// The symmetry is a number from 0 to 7, with 0 being the identity. Bit 0
+    /// reflects the board left to right, bit 1 reflects the board top to
+    /// bottom, and bit 2 reflects the board in its leading diagonal, with the
+    /// reflections applied in that order.
+    pub fn transform(&self, symmetry: u8) -> Board {
+        let mut board = self.0;
+
+        if symmetry & 0b001 != 0 {
+            // Reverse the bits within each row.
+            board = (board & 0x5555) << 1 | (board >> 1) & 0x5555;
+            board = (board & 0x3333) << 2 | (board >> 2) & 0x3333;
+        }
+
+        if symmetry & 0b010 != 0 {
+            // Reverse the order of the rows.
+            board = board.swap_bytes();
+            board = (board & 0x0f0f) << 4 | (board >> 4) & 0x0f0f;
+        }
+
+        if symmetry & 0b100 != 0 {
+            // Swap squares across the diagonal within each 2×2 block, and then
+            // swap the 2×2 blocks across the diagonal.
+            let swap = (board ^ (board >> 3)) & 0x0a0a;
+            board ^= swap ^ (swap << 3);
+            let swap = (board ^ (board >> 6)) & 0x00cc;
+            board ^= swap ^ (swap << 6);
+        }
+
+        Board(board)
+    }
+
+    /// Returns the symmetry that undoes the specified symmetry, as described for
+    /// `transform`.
+    ///
+    /// Reflections alone are their own inverses. When the board is also
+    /// reflected in its leading diagonal, the inverse reflects the board in the
+    /// diagonal first, which swaps the left to right and top to bottom
+    /// reflections.
+    pub fn inverse_symmetry(symmetry: u8) -> u8 {
+        if symmetry & 0b100 == 0 {
+            symmetry
+        } else {
+            0b100 | (symmetry & 0b001) << 1 | (symmetry & 0b010) >> 1
+        }
+    }
+
     /// Returns an instance with each square marked as occupied if it is ocupied
     /// in either of the specified boards.
     pub fn merge(board_1: Board, board_2: Board) -> Board {
@@ -185,8 +238,8 @@ mod tests {
 
     #[test]
     fn is_available() {
-        assert!(Board::from(0b_0000_0000_000_0000).is_available(0));
-        assert!(!Board::from(0b_0000_0000_000_0001).is_available(0));
+        assert!(Board::from(0b_0000_0000_0000_0000).is_available(0));
+        assert!(!Board::from(0b_0000_0000_0000_0001).is_available(0));
     }
 
     #[test]
@@ -197,6 +250,74 @@ mod tests {
         )
     }
 
+    #[test]
+    fn transform() {
+        // Squares 1, 2, 6, and 11 in the pattern:
+        //
+        // □ ■ ■ □
+        // □ □ ■ □
+        // □ □ □ ■
+        // □ □ □ □
+        let board = Board::from(0b_0000_1000_0100_0110);
+
+        let transformed = [
+            0b_0000_1000_0100_0110,
+            0b_0000_0001_0010_0110,
+            0b_0110_0100_1000_0000,
+            0b_0110_0010_0001_0000,
+            0b_0100_0011_0001_0000,
+            0b_0000_0001_0011_0100,
+            0b_0010_1100_1000_0000,
+            0b_0000_1000_1100_0010,
+        ];
+
+        for (symmetry, expected) in transformed.into_iter().enumerate() {
+            assert_eq!(expected, u16::from(board.transform(symmetry as u8)));
+        }
+    }
+
+    #[test]
+    fn transform_square() {
+        // Each symmetry must map every single square to the square given by
+        // transforming its row and column.
+        for symmetry in 0..8 {
+            for square in 0..16 {
+                let (mut row, mut column) = (square / 4, square % 4);
+
+                if symmetry & 0b001 != 0 {
+                    column = 3 - column;
+                }
+
+                if symmetry & 0b010 != 0 {
+                    row = 3 - row;
+                }
+
+                if symmetry & 0b100 != 0 {
+                    (row, column) = (column, row);
+                }
+
+                assert_eq!(
+                    Board::default().with(row * 4 + column),
+                    Board::default().with(square).transform(symmetry)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_symmetry() {
+        let board = Board::from(0b_0000_1000_0100_0110);
+
+        for symmetry in 0..8 {
+            assert_eq!(
+                board,
+                board
+                    .transform(symmetry)
+                    .transform(Board::inverse_symmetry(symmetry))
+            );
+        }
+    }
+
     #[test]
     fn merge() {
         assert_eq!(
@@ -0,0 +1,406 @@
+//! Defines a struct representing an opening book.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::board::Board;
+use crate::format::{self, FileError, FileKind, Header};
+use crate::game::Game;
+use crate::moves::Move;
+use crate::parallel;
+use crate::solver;
+use crate::status::Status;
+
+/// Describes an opening book file.
+const KIND: FileKind = FileKind {
+    name: "an opening book",
+    magic: *b"MLOB",
+    version: 2,
+};
+
+/// Represents an opening book, which stores the victory status of every
+/// position reachable in the first few moves from a starting position, and
+/// every move achieving that status.
+///
+/// Positions are stored under their canonical instance, and the moves stored
+/// are transformed back when a position is looked up, so symmetric positions
+/// share an entry.
+///
+/// An opening book is written to a file as a 32 byte header, in the format
+/// described in the `format` module, containing the bytes `MLOB`, the number of
+/// moves covered, and these fields:
+///
+/// 1. A fingerprint of the rules compiled into `Tile::MOVES` and
+///    `Board::LINES`, so that a book generated under different rules is
+///    rejected.
+/// 2. The number of entries.
+/// 3. A 64 bit FNV-1a checksum of the bytes of the entries.
+///
+/// Each entry follows, sorted by key, as:
+///
+/// 1. The position's key, as returned by `Game::key`, as a little-endian
+///    `u128`.
+/// 2. The status as a byte: 0 for a loss, 1 for a draw, or 2 for a win.
+/// 3. The number of best moves as a byte.
+/// 4. Each best move as a byte, containing the tile discriminant multiplied by
+///    16 plus the square, or 64 for a pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningBook {
+    /// The number of moves from the starting position covered by the book.
+    plies: u8,
+
+    /// The entries, sorted by key.
+    entries: Vec<Entry>,
+}
+
+/// Represents an entry in an opening book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// The key of the canonical position.
+    key: u128,
+
+    /// The victory status of the position.
+    status: Status,
+
+    /// The moves in the canonical position achieving the status, in order of
+    /// preference.
+    moves: Vec<Move>,
+}
+
+impl OpeningBook {
+    /// Returns the opening book of every position reachable from the specified
+    /// position in fewer than the specified number of moves (counting passes).
+    ///
+    /// The positions reached after the specified number of moves are solved
+    /// with `parallel::solve_parallel` using the specified number of threads,
+    /// and the status of each earlier position is found from the statuses of
+    /// the positions after its moves, so every move is solved exactly once.
+    ///
+    /// The fourth parameter is updated with a count of the number games
+    /// analysed.
+    pub fn generate(game: Game, plies: u8, threads: usize, games: &mut u64) -> Self {
+        let canonical = game.canonical();
+        let mut layers = vec![HashMap::from([(canonical.key(), canonical)])];
+
+        for ply in 0..plies as usize {
+            let mut next = HashMap::new();
+
+            for game in layers[ply].values() {
+                for player_move in solver::get_moves(game) {
+                    let after = game.after(player_move).canonical();
+                    next.insert(after.key(), after);
+                }
+            }
+
+            layers.push(next);
+        }
+
+        let mut statuses: HashMap<u128, Status> = layers
+            .pop()
+            .expect("there is at least one layer")
+            .into_iter()
+            .map(|(key, game)| (key, parallel::solve_parallel(game, threads, games)))
+            .collect();
+
+        let mut entries = Vec::new();
+
+        for layer in layers.iter().rev() {
+            for (&key, game) in layer {
+                if game.is_over() {
+                    *games += 1;
+                    statuses.insert(key, game.get_status());
+                    continue;
+                }
+
+                let moves: Vec<(Move, Status)> = solver::get_moves(game)
+                    .into_iter()
+                    .map(|player_move| {
+                        let after = game.after(player_move).canonical().key();
+                        (player_move, !statuses[&after])
+                    })
+                    .collect();
+
+                let status = moves
+                    .iter()
+                    .map(|&(_, status)| status)
+                    .max_by_key(|&status| format::encode_status(status))
+                    .expect("a game that is not over has a move");
+
+                statuses.insert(key, status);
+                entries.push(Entry {
+                    key,
+                    status,
+                    moves: moves
+                        .into_iter()
+                        .filter(|&(_, move_status)| move_status == status)
+                        .map(|(player_move, _)| player_move)
+                        .collect(),
+                });
+            }
+        }
+
+        entries.sort_unstable_by_key(|entry| entry.key);
+        OpeningBook { plies, entries }
+    }
+
+    /// Returns the number of moves from the starting position covered by the
+    /// book.
+    pub fn plies(&self) -> u8 {
+        self.plies
+    }
+
+    /// Returns the number of positions stored.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no positions are stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the victory status of the specified position and every move
+    /// achieving it in order of preference, or `None` if the position is not
+    /// stored.
+    pub fn get(&self, game: &Game) -> Option<(Status, Vec<Move>)> {
+        let symmetry = game.canonical_symmetry();
+        let key = game.transform(symmetry).key();
+
+        let entry = &self.entries[self
+            .entries
+            .binary_search_by_key(&key, |entry| entry.key)
+            .ok()?];
+
+        // The moves are stored for the canonical instance, so are transformed
+        // back.
+        let inverse = Board::inverse_symmetry(symmetry);
+
+        Some((
+            entry.status,
+            entry
+                .moves
+                .iter()
+                .map(|player_move| player_move.transform(inverse))
+                .collect(),
+        ))
+    }
+
+    /// Returns a copy of the book storing the specified status and moves for
+    /// the specified position, replacing any entry already stored for it.
+    ///
+    /// This lets tests store moves that `generate` would not choose, so that
+    /// they can tell whether a move was looked up.
+    #[doc(hidden)]
+    pub fn with_entry(&self, game: &Game, status: Status, moves: &[Move]) -> Self {
+        // The moves are stored for the canonical instance, so are transformed
+        // to match it.
+        let symmetry = game.canonical_symmetry();
+        let key = game.transform(symmetry).key();
+
+        let mut entries = self.entries.clone();
+        entries.retain(|entry| entry.key != key);
+        entries.insert(
+            entries.partition_point(|entry| entry.key < key),
+            Entry {
+                key,
+                status,
+                moves: moves
+                    .iter()
+                    .map(|player_move| player_move.transform(symmetry))
+                    .collect(),
+            },
+        );
+
+        OpeningBook {
+            plies: self.plies,
+            entries,
+        }
+    }
+
+    /// Writes the opening book in the format described for `OpeningBook`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut bytes = Vec::new();
+
+        for entry in &self.entries {
+            bytes.extend(entry.key.to_le_bytes());
+            bytes.extend([format::encode_status(entry.status), entry.moves.len() as u8]);
+            bytes.extend(entry.moves.iter().copied().map(format::encode_move));
+        }
+
+        let header = Header {
+            parameter: self.plies,
+            fields: [
+                format::rules_fingerprint(),
+                self.entries.len() as u64,
+                format::fnv1a(bytes.iter().copied()),
+            ],
+        };
+        header.write(&KIND, &mut writer)?;
+        writer.write_all(&bytes)?;
+        writer.flush()
+    }
+
+    /// Reads an opening book in the format described for `OpeningBook`,
+    /// checking that the header is valid and matches the compiled rules, that
+    /// the entries match the checksum, and that the entries are sorted and
+    /// contain valid statuses and moves.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, FileError> {
+        let invalid = FileError::InvalidFormat;
+
+        let Header {
+            parameter: plies,
+            fields: [fingerprint, count, expected_checksum],
+        } = Header::read(&KIND, &mut reader)?;
+
+        if fingerprint != format::rules_fingerprint() {
+            return Err(FileError::RulesMismatch);
+        }
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if format::fnv1a(bytes.iter().copied()) != expected_checksum {
+            return Err(FileError::ChecksumMismatch);
+        }
+
+        let truncated = || invalid("the entries are truncated".to_string());
+        let mut bytes = bytes.as_slice();
+        let mut entries = Vec::new();
+
+        for _ in 0..count {
+            let (key, rest) = bytes.split_first_chunk::<16>().ok_or_else(truncated)?;
+            let ([status, move_count], rest) =
+                rest.split_first_chunk::<2>().ok_or_else(truncated)?;
+            let (moves, rest) = rest
+                .split_at_checked(*move_count as usize)
+                .ok_or_else(truncated)?;
+
+            entries.push(Entry {
+                key: u128::from_le_bytes(*key),
+                status: format::decode_status(*status)
+                    .ok_or_else(|| invalid(format!("invalid status {status}")))?,
+                moves: moves
+                    .iter()
+                    .map(|&code| {
+                        format::decode_move(code)
+                            .ok_or_else(|| invalid(format!("invalid move {code}")))
+                    })
+                    .collect::<Result<_, _>>()?,
+            });
+
+            bytes = rest;
+        }
+
+        if !bytes.is_empty() {
+            return Err(invalid(format!(
+                "found {} bytes after the entries",
+                bytes.len()
+            )));
+        }
+
+        if entries.windows(2).any(|pair| pair[0].key >= pair[1].key) {
+            return Err(invalid("the entries are not sorted".to_string()));
+        }
+
+        Ok(OpeningBook { plies, entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::create_small_game;
+    use crate::tile::Tile;
+
+    #[test]
+    fn generate() {
+        let game = create_small_game();
+        let book = OpeningBook::generate(game, 2, 2, &mut 0);
+        assert_eq!(2, book.plies());
+
+        let (status, moves) = book.get(&game).unwrap();
+        assert_eq!(solver::solve(game, &mut 0), status);
+
+        // Every move stored achieves the status, and every other move does
+        // not.
+        for player_move in solver::get_moves(&game) {
+            assert_eq!(
+                moves.contains(&player_move),
+                !solver::solve(game.after(player_move), &mut 0) == status
+            );
+        }
+
+        // Positions after the moves covered are not stored.
+        let after = game.after(moves[0]);
+        let (_, replies) = book.get(&after).unwrap();
+        assert_eq!(None, book.get(&after.after(replies[0])));
+    }
+
+    #[test]
+    fn get() {
+        let game = create_small_game();
+        let book = OpeningBook::generate(game, 2, 1, &mut 0);
+        let after = game.after(Move::Play(Tile::Pusher, 1));
+
+        // The moves stored for each symmetric instance are legal and achieve
+        // the stored status.
+        for symmetry in 0..8 {
+            let transformed = after.transform(symmetry);
+            let (status, moves) = book.get(&transformed).unwrap();
+
+            for player_move in moves {
+                let next = transformed.try_after(player_move).unwrap();
+                assert_eq!(!status, solver::solve(next, &mut 0));
+            }
+        }
+    }
+
+    #[test]
+    fn with_entry() {
+        let game = create_small_game().after(Move::Play(Tile::Pusher, 1));
+        let book = OpeningBook::generate(create_small_game(), 2, 1, &mut 0);
+        let moves = vec![Move::Play(Tile::Straight, 15)];
+        let changed = book.with_entry(&game, Status::Draw, &moves);
+
+        assert_eq!(book.len(), changed.len());
+        assert_eq!(Some((Status::Draw, moves)), changed.get(&game));
+
+        // The entries stay sorted, so a new position can be found.
+        let added = changed.with_entry(&Game::default(), Status::Win, &[]);
+        assert_eq!(book.len() + 1, added.len());
+        assert_eq!(Some((Status::Win, Vec::new())), added.get(&Game::default()));
+        assert_eq!(changed.get(&game), added.get(&game));
+    }
+
+    #[test]
+    fn write_and_read() {
+        let book = OpeningBook::generate(create_small_game(), 2, 1, &mut 0);
+
+        let mut bytes = Vec::new();
+        book.write(&mut bytes).unwrap();
+        assert_eq!(book, OpeningBook::read(bytes.as_slice()).unwrap());
+
+        let mut truncated = bytes.clone();
+        truncated.pop();
+        assert!(matches!(
+            OpeningBook::read(truncated.as_slice()),
+            Err(FileError::ChecksumMismatch)
+        ));
+
+        let mut other_rules = bytes.clone();
+        other_rules[8] ^= 1;
+        assert!(matches!(
+            OpeningBook::read(other_rules.as_slice()),
+            Err(FileError::RulesMismatch)
+        ));
+
+        // An invalid status with a matching checksum.
+        bytes[Header::<3>::BYTES + 16] = 3;
+        let checksum = format::fnv1a(bytes[Header::<3>::BYTES..].iter().copied());
+        bytes[24..32].copy_from_slice(&checksum.to_le_bytes());
+        assert_eq!(
+            "invalid format: invalid status 3",
+            OpeningBook::read(bytes.as_slice()).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,529 @@
+//! Parses the command line arguments.
+
+use std::thread;
+use std::time::Duration;
+
+use mijnlieff_solver::notation::NotationError;
+use mijnlieff_solver::{BoardGeometry, Game, Hand};
+
+/// The usage message.
+pub const USAGE: &str = "\
+Usage: mijnlieff-solver [COMMAND] [OPTIONS]
+
+Commands:
+  solve                  Solve the position (the default command)
+  analyse                Solve the position, showing the margin, a line of
+                         perfect play, and the outcome of every move
+  play [first|second]    Play against the solver, moving first by default
+  perft <depth>          Count the positions in the game tree to a depth
+  bench                  Time the solver on a set of positions
+  tablebase <tiles> <file>
+                         Write a tablebase of the positions reachable from the
+                         position with at most the specified number of tiles
+                         remaining
+  tablebase verify <file>
+                         Check every position in a tablebase against the solver
+  database <file>        Solve the position, writing every position solved
+                         with at least 7 tiles remaining to a database
+  book <moves> <file>    Write an opening book of the positions reachable from
+                         the position in fewer than the specified number of
+                         moves
+
+Options:
+  --position <position>  The position, such as '..../..../..../.... 2222 2222
+                         0660' (see the notation module for details)
+  --board <board>        Solve on a variant board, such as '0777 3' for a 3×3
+                         board or '0,0 0,2 0,4 0,6 3' for the four quarters
+                         in a 2×8 strip (see the notation module for details)
+  --hands <hands>        Start with the specified hands for the first and
+                         second players, such as '0323 2222' for no Pullers
+                         and an extra Pusher and Diagonal for the first player
+  --tablebase <file>     Probe a tablebase when solving, on a single thread
+  --progress             Report progress to stderr while solving
+  --max-games <count>    Stop solving after analysing the specified number of
+                         games (exactly, with one thread), and show the status
+                         of each move proven before stopping
+  --database <file>      Look up positions in a database when playing or
+                         analysing
+  --book <file>          Look up the computer's moves in an opening book when
+                         playing
+  --threads <count>      The number of threads used by solve, bench, book, and
+                         tablebase, which defaults to the number of available
+                         CPUs
+  --format <text|json>   The output format, which defaults to text
+  --time-limit <seconds> Stop with an error if the command takes longer than
+                         the specified time, except that solve stops and
+                         shows the status of each move proven before stopping
+  -h, --help             Show this message";
+
+/// Represents the command to run.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Solve,
+    Analyse,
+    Play { user_first: bool },
+    Perft { depth: usize },
+    Bench,
+    Tablebase { tiles: u8, path: String },
+    VerifyTablebase { path: String },
+    Database { path: String },
+    Book { plies: u8, path: String },
+    Help,
+}
+
+/// Represents the output format.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Represents the command and the options specified by the arguments.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    /// The command to run.
+    pub command: Command,
+
+    /// The position specified with `--position`, if any.
+    pub position: Option<Game>,
+
+    /// The board geometry specified with `--board`, if any.
+    pub board: Option<BoardGeometry>,
+
+    /// The starting hands of the first and second players specified with
+    /// `--hands`, if any.
+    pub hands: Option<(Hand, Hand)>,
+
+    /// The path of the tablebase specified with `--tablebase`, if any.
+    pub tablebase: Option<String>,
+
+    /// Whether `--progress` was specified.
+    pub progress: bool,
+
+    /// The maximum number of games analysed specified with `--max-games`, if
+    /// any.
+    pub max_games: Option<u64>,
+
+    /// The path of the database specified with `--database`, if any.
+    pub database: Option<String>,
+
+    /// The path of the opening book specified with `--book`, if any.
+    pub book: Option<String>,
+
+    /// The number of threads to use.
+    pub threads: usize,
+
+    /// The output format.
+    pub format: Format,
+
+    /// The time after which the command is stopped, if any.
+    pub time_limit: Option<Duration>,
+}
+
+/// Returns the options specified by the arguments (excluding the program
+/// name), or an error describing the problem with the arguments.
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut args = args.iter().map(String::as_str).peekable();
+
+    let command = match args.next_if(|arg| !arg.starts_with('-')) {
+        None | Some("solve") => Command::Solve,
+        Some("analyse" | "analyze") => Command::Analyse,
+        Some("play") => Command::Play {
+            user_first: match args.next_if(|arg| !arg.starts_with('-')) {
+                None | Some("first") => true,
+                Some("second") => false,
+                Some(order) => {
+                    return Err(format!("expected 'first' or 'second', found '{order}'"))
+                }
+            },
+        },
+        Some("perft") => Command::Perft {
+            depth: parse_value("depth", args.next_if(|arg| !arg.starts_with('-')))?,
+        },
+        Some("bench") => Command::Bench,
+        Some("tablebase") => match args.next_if(|arg| !arg.starts_with('-')) {
+            Some("verify") => Command::VerifyTablebase {
+                path: args.next().ok_or("missing tablebase file")?.to_string(),
+            },
+            tiles => Command::Tablebase {
+                tiles: parse_value("tile count", tiles)?,
+                path: args.next().ok_or("missing tablebase file")?.to_string(),
+            },
+        },
+        Some("database") => Command::Database {
+            path: args.next().ok_or("missing database file")?.to_string(),
+        },
+        Some("book") => Command::Book {
+            plies: parse_value("move count", args.next_if(|arg| !arg.starts_with('-')))?,
+            path: args.next().ok_or("missing opening book file")?.to_string(),
+        },
+        Some(command) => return Err(format!("unknown command '{command}'")),
+    };
+
+    let mut options = Options {
+        command,
+        position: None,
+        board: None,
+        hands: None,
+        tablebase: None,
+        progress: false,
+        max_games: None,
+        database: None,
+        book: None,
+        threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        format: Format::Text,
+        time_limit: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg {
+            "--position" => {
+                let position = args.next().ok_or("missing value for --position")?;
+                options.position = Some(
+                    position
+                        .parse()
+                        .map_err(|error: NotationError| error.to_string())?,
+                );
+            }
+            "--board" => {
+                let board = args.next().ok_or("missing value for --board")?;
+                options.board = Some(
+                    board
+                        .parse()
+                        .map_err(|error: NotationError| error.to_string())?,
+                );
+            }
+            "--hands" => {
+                let hands = args.next().ok_or("missing value for --hands")?;
+
+                let [hand, opponent_hand] = hands
+                    .split_whitespace()
+                    .map(|hand| hand.parse().map_err(|_| format!("invalid hand '{hand}'")))
+                    .collect::<Result<Vec<Hand>, _>>()?
+                    .try_into()
+                    .map_err(|_| format!("expected 2 hands, found '{hands}'"))?;
+
+                options.hands = Some((hand, opponent_hand));
+            }
+            "--tablebase" => {
+                let path = args.next().ok_or("missing value for --tablebase")?;
+                options.tablebase = Some(path.to_string());
+            }
+            "--progress" => options.progress = true,
+            "--max-games" => options.max_games = Some(parse_value("game count", args.next())?),
+            "--database" => {
+                let path = args.next().ok_or("missing value for --database")?;
+                options.database = Some(path.to_string());
+            }
+            "--book" => {
+                let path = args.next().ok_or("missing value for --book")?;
+                options.book = Some(path.to_string());
+            }
+            "--threads" => {
+                options.threads = parse_value("thread count", args.next())?;
+
+                if options.threads == 0 {
+                    return Err("the thread count must be at least 1".to_string());
+                }
+            }
+            "--format" => {
+                options.format = match args.next() {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    Some(format) => return Err(format!("unknown format '{format}'")),
+                    None => return Err("missing value for --format".to_string()),
+                }
+            }
+            "--time-limit" => {
+                let seconds: f64 = parse_value("time limit", args.next())?;
+
+                options.time_limit = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("invalid time limit '{seconds}'"))?,
+                );
+            }
+            "-h" | "--help" => options.command = Command::Help,
+            arg => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    if options.time_limit.is_some() && matches!(options.command, Command::Play { .. }) {
+        return Err("--time-limit cannot be used with play".to_string());
+    }
+
+    if options.hands.is_some() && options.position.is_some() {
+        return Err("--hands cannot be used with --position".to_string());
+    }
+
+    if options.tablebase.is_some() && !matches!(options.command, Command::Solve | Command::Help) {
+        return Err("--tablebase can only be used with solve".to_string());
+    }
+
+    if options.progress && !matches!(options.command, Command::Solve | Command::Help) {
+        return Err("--progress can only be used with solve".to_string());
+    }
+
+    if options.progress && (options.board.is_some() || options.tablebase.is_some()) {
+        return Err("--progress cannot be used with --board or --tablebase".to_string());
+    }
+
+    if options.max_games.is_some() && !matches!(options.command, Command::Solve | Command::Help) {
+        return Err("--max-games can only be used with solve".to_string());
+    }
+
+    if options.max_games.is_some() && (options.board.is_some() || options.tablebase.is_some()) {
+        return Err("--max-games cannot be used with --board or --tablebase".to_string());
+    }
+
+    if options.database.is_some()
+        && !matches!(
+            options.command,
+            Command::Play { .. } | Command::Analyse | Command::Help
+        )
+    {
+        return Err("--database can only be used with play and analyse".to_string());
+    }
+
+    if options.book.is_some() && !matches!(options.command, Command::Play { .. } | Command::Help) {
+        return Err("--book can only be used with play".to_string());
+    }
+
+    if options.tablebase.is_some() && options.board.is_some() {
+        return Err("--tablebase cannot be used with --board".to_string());
+    }
+
+    if options.board.is_some() && !matches!(options.command, Command::Solve | Command::Help) {
+        return Err("--board can only be used with solve".to_string());
+    }
+
+    Ok(options)
+}
+
+impl Options {
+    /// Returns the position specified with `--position`, or else the specified
+    /// initial position with the hands specified with `--hands`, if any.
+    pub fn start(&self, initial: Game) -> Game {
+        match (self.position, self.hands) {
+            (Some(game), _) => game,
+            (None, Some((hand, opponent_hand))) => initial.with_hands(hand, opponent_hand),
+            (None, None) => initial,
+        }
+    }
+}
+
+/// Returns the parsed value of an argument, or an error naming the value if it
+/// is missing or invalid.
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<&str>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {name} '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &str) -> Result<Options, String> {
+        parse(
+            &args
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::Solve, parse_str("").unwrap().command);
+        assert_eq!(Command::Solve, parse_str("solve").unwrap().command);
+        assert_eq!(Command::Analyse, parse_str("analyse").unwrap().command);
+        assert_eq!(
+            Command::Play { user_first: true },
+            parse_str("play").unwrap().command
+        );
+        assert_eq!(
+            Command::Play { user_first: false },
+            parse_str("play second").unwrap().command
+        );
+        assert_eq!(
+            Command::Perft { depth: 4 },
+            parse_str("perft 4").unwrap().command
+        );
+        assert_eq!(Command::Bench, parse_str("bench").unwrap().command);
+        assert_eq!(
+            Command::Tablebase {
+                tiles: 6,
+                path: "endgame.tb".to_string()
+            },
+            parse_str("tablebase 6 endgame.tb").unwrap().command
+        );
+        assert_eq!(
+            Command::VerifyTablebase {
+                path: "endgame.tb".to_string()
+            },
+            parse_str("tablebase verify endgame.tb").unwrap().command
+        );
+        assert_eq!(
+            Command::Database {
+                path: "game.db".to_string()
+            },
+            parse_str("database game.db").unwrap().command
+        );
+        assert_eq!(
+            Command::Book {
+                plies: 3,
+                path: "opening.book".to_string()
+            },
+            parse_str("book 3 opening.book").unwrap().command
+        );
+        assert_eq!(Command::Help, parse_str("solve --help").unwrap().command);
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = parse_str("bench --threads 3 --format json --time-limit 1.5").unwrap();
+        assert_eq!(None, options.position);
+        assert!(!options.progress);
+        assert_eq!(None, options.max_games);
+        assert_eq!(3, options.threads);
+        assert_eq!(Format::Json, options.format);
+        assert_eq!(Some(Duration::from_millis(1500)), options.time_limit);
+
+        let options = parse_str("solve --progress --max-games 1000 --threads 2").unwrap();
+        assert!(options.progress);
+        assert_eq!(Some(1000), options.max_games);
+        assert_eq!(2, options.threads);
+
+        let options = parse(&[
+            "analyse".to_string(),
+            "--position".to_string(),
+            "..../..../..../.... 2222 2222 0660".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(Some(Game::initial()), options.position);
+        assert_eq!(None, options.board);
+
+        let options = parse(&[
+            "play".to_string(),
+            "--hands".to_string(),
+            "0323 2222".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            Some((Hand::new([0, 3, 2, 3]), Hand::default())),
+            options.hands
+        );
+        assert_eq!(
+            Game::initial().with_hands(Hand::new([0, 3, 2, 3]), Hand::default()),
+            options.start(Game::initial())
+        );
+
+        let options = parse(&["--board".to_string(), "0777 3".to_string()]).unwrap();
+        assert_eq!(Some(BoardGeometry::new(0x0777.into(), 3)), options.board);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err("unknown command 'foo'".to_string()), parse_str("foo"));
+        assert_eq!(Err("missing depth".to_string()), parse_str("perft"));
+        assert_eq!(Err("invalid depth 'x'".to_string()), parse_str("perft x"));
+        assert_eq!(
+            Err("missing tablebase file".to_string()),
+            parse_str("tablebase 6")
+        );
+        assert_eq!(
+            Err("--tablebase can only be used with solve".to_string()),
+            parse_str("analyse --tablebase endgame.tb")
+        );
+        assert_eq!(
+            Err("--database can only be used with play and analyse".to_string()),
+            parse_str("solve --database game.db")
+        );
+        assert_eq!(
+            Err("--progress can only be used with solve".to_string()),
+            parse_str("bench --progress")
+        );
+        assert_eq!(
+            Err("--progress cannot be used with --board or --tablebase".to_string()),
+            parse_str("solve --progress --tablebase endgame.tb")
+        );
+        assert_eq!(
+            Err("--max-games can only be used with solve".to_string()),
+            parse_str("analyse --max-games 1000")
+        );
+        assert_eq!(
+            Err("--max-games cannot be used with --board or --tablebase".to_string()),
+            parse_str("solve --max-games 1000 --tablebase endgame.tb")
+        );
+        assert_eq!(
+            Err("invalid game count 'lots'".to_string()),
+            parse_str("solve --max-games lots")
+        );
+        assert_eq!(
+            Err("--book can only be used with play".to_string()),
+            parse_str("analyse --book opening.book")
+        );
+        assert_eq!(
+            Err("missing opening book file".to_string()),
+            parse_str("book 3")
+        );
+        assert_eq!(
+            Err("expected 'first' or 'second', found 'third'".to_string()),
+            parse_str("play third")
+        );
+        assert_eq!(
+            Err("the thread count must be at least 1".to_string()),
+            parse_str("solve --threads 0")
+        );
+        assert_eq!(
+            Err("unknown format 'xml'".to_string()),
+            parse_str("solve --format xml")
+        );
+        assert_eq!(
+            Err("invalid time limit '-1'".to_string()),
+            parse_str("solve --time-limit -1")
+        );
+        assert_eq!(
+            Err("--time-limit cannot be used with play".to_string()),
+            parse_str("play --time-limit 10")
+        );
+        assert_eq!(
+            Err("unexpected argument 'extra'".to_string()),
+            parse_str("solve --format text extra")
+        );
+        assert_eq!(
+            Err("--board can only be used with solve".to_string()),
+            parse(&[
+                "bench".to_string(),
+                "--board".to_string(),
+                "0777 3".to_string()
+            ])
+        );
+        assert_eq!(
+            Err("invalid hand '22x2'".to_string()),
+            parse(&["--hands".to_string(), "22x2 2222".to_string()])
+        );
+        assert_eq!(
+            Err("expected 2 hands, found '2222'".to_string()),
+            parse(&["--hands".to_string(), "2222".to_string()])
+        );
+        assert_eq!(
+            Err("--hands cannot be used with --position".to_string()),
+            parse(&[
+                "--hands".to_string(),
+                "2222 2222".to_string(),
+                "--position".to_string(),
+                "..../..../..../.... 2222 2222 0660".to_string()
+            ])
+        );
+        assert_eq!(
+            Err("invalid position: expected 4 or 5 fields, found 1".to_string()),
+            parse_str("solve --position nonsense")
+        );
+        assert_eq!(
+            Err("invalid board: expected 2 or 5 fields, found 1".to_string()),
+            parse_str("solve --board nonsense")
+        );
+    }
+}
@@ -0,0 +1,621 @@
+//! Provides the command line interface.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mijnlieff_solver::analysis;
+use mijnlieff_solver::database;
+use mijnlieff_solver::notation;
+use mijnlieff_solver::parallel;
+use mijnlieff_solver::perft;
+use mijnlieff_solver::solver;
+use mijnlieff_solver::{
+    Game, Limits, Move, OpeningBook, Progress, SolutionDatabase, SolveOutcome, Status, Tablebase,
+};
+
+use args::{Command, Format, Options};
+
+mod args;
+mod play;
+
+/// The time between progress reports when solving with `--progress`.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Game records of the positions solved by the `bench` command, each of which
+/// takes between 12 and 16 million games to solve.
+const BENCH_RECORDS: [&str; 4] = [
+    "P a4, P a1, L b4, P c4",
+    "L d1, S c1, S c3, P c4",
+    "S b1, P d1, D c3, S b4",
+    "P b1, P d1, D a1, P d4",
+];
+
+/// Runs the command specified by the arguments (excluding the program name).
+pub fn run(args: &[String]) {
+    let options = args::parse(args).unwrap_or_else(|error| {
+        eprintln!("Error: {error}");
+        eprintln!("Run with --help for usage");
+        process::exit(2);
+    });
+
+    // A solve with limits stops itself at the time limit, showing the moves
+    // proven before stopping.
+    if let Some(time_limit) = options.time_limit {
+        if !has_limits(&options) {
+            start_timer(time_limit, options.format);
+        }
+    }
+
+    match options.command {
+        Command::Solve => run_solve(&options),
+        Command::Analyse => run_analyse(&options),
+        Command::Play { user_first } => {
+            let game = options.start(Game::initial());
+            let book = options.book.as_deref().map(read_book);
+            let database = options.database.as_deref().map(read_database);
+
+            if let Err(error) = play::play(
+                game,
+                user_first,
+                book.as_ref(),
+                database.as_ref(),
+                io::stdin().lock(),
+                io::stdout().lock(),
+            ) {
+                eprintln!("Error: {error}");
+                process::exit(1);
+            }
+        }
+        Command::Perft { depth } => run_perft(&options, depth),
+        Command::Bench => run_bench(&options),
+        Command::Tablebase { tiles, ref path } => run_tablebase(&options, tiles, path),
+        Command::VerifyTablebase { ref path } => run_verify_tablebase(&options, path),
+        Command::Database { ref path } => run_database(&options, path),
+        Command::Book { plies, ref path } => run_book(&options, plies, path),
+        Command::Help => println!("{}", args::USAGE),
+    }
+}
+
+/// Returns whether the command is a solve that can stop at the limits given by
+/// `--max-games` and `--time-limit`, which is not the case for solves on a
+/// variant board or probing a tablebase.
+fn has_limits(options: &Options) -> bool {
+    matches!(options.command, Command::Solve)
+        && options.board.is_none()
+        && options.tablebase.is_none()
+}
+
+/// Starts a thread that exits with an error once the time limit has passed.
+fn start_timer(time_limit: Duration, format: Format) {
+    thread::spawn(move || {
+        thread::sleep(time_limit);
+
+        let error = format!(
+            "time limit of {} seconds exceeded",
+            time_limit.as_secs_f64()
+        );
+
+        match format {
+            Format::Text => eprintln!("Error: {error}"),
+            Format::Json => println!("{{\"error\":\"{error}\"}}"),
+        }
+
+        process::exit(3);
+    });
+}
+
+/// Solves the position using multiple threads, or on a single thread if a
+/// variant board or a tablebase is specified, and outputs the result, the
+/// number of games analysed, and the time taken.
+///
+/// If the solve stops at the limits given by `--max-games` or `--time-limit`,
+/// the status of each move proven before stopping is output instead of the
+/// result.
+fn run_solve(options: &Options) {
+    let tablebase = options.tablebase.as_deref().map(read_tablebase);
+    let limited = options.max_games.is_some() || options.time_limit.is_some();
+
+    let now = Instant::now();
+    let mut games = 0;
+
+    let (game, outcome) = match (&options.board, &tablebase) {
+        (Some(geometry), _) => {
+            let game = options.position.map_or_else(
+                || options.start(geometry.initial_game()),
+                |game| geometry.restrict(game),
+            );
+
+            (
+                game,
+                SolveOutcome::Solved(solver::solve_on(game, geometry, &mut games)),
+            )
+        }
+        (None, Some(tablebase)) => {
+            let game = options.start(Game::default());
+            (
+                game,
+                SolveOutcome::Solved(solver::solve_with_tablebase(game, tablebase, &mut games)),
+            )
+        }
+        (None, None) => {
+            let game = options.start(Game::default());
+            let mut limits = Limits::default();
+
+            if let Some(max_games) = options.max_games {
+                limits = limits.with_max_games(max_games);
+            }
+
+            if let Some(time_limit) = options.time_limit {
+                limits = limits.with_time_limit(time_limit);
+            }
+
+            let interval = if options.progress {
+                PROGRESS_INTERVAL
+            } else {
+                Duration::MAX
+            };
+
+            (
+                game,
+                parallel::solve_parallel_with_progress(
+                    game,
+                    options.threads,
+                    &limits,
+                    &mut games,
+                    interval,
+                    report_progress,
+                ),
+            )
+        }
+    };
+
+    let seconds = now.elapsed().as_secs_f64();
+
+    let proven = match &outcome {
+        SolveOutcome::Solved(_) => &[][..],
+        SolveOutcome::Stopped(proven) => proven,
+    };
+
+    match (options.format, outcome.status()) {
+        (Format::Text, Some(status)) => {
+            println!("Analysed {games} games in {seconds:.0} seconds");
+            println!("{}", describe(&game, status));
+        }
+        (Format::Text, None) => {
+            println!("Stopped after analysing {games} games in {seconds:.0} seconds");
+            println!(
+                "Proven moves: {}",
+                if proven.is_empty() {
+                    "none".to_string()
+                } else {
+                    proven
+                        .iter()
+                        .map(|(player_move, status)| {
+                            format!("{player_move} ({})", status_name(*status))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            );
+        }
+        (Format::Json, status) if limited => println!(
+            "{{\"status\":\"{}\",\"proven\":[{}],\"games\":{games},\"seconds\":{seconds}}}",
+            status.map_or("unknown", status_name),
+            proven
+                .iter()
+                .map(|(player_move, status)| format!(
+                    "{{\"move\":\"{player_move}\",\"status\":\"{}\"}}",
+                    status_name(*status)
+                ))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        (Format::Json, status) => println!(
+            "{{\"status\":\"{}\",\"games\":{games},\"seconds\":{seconds}}}",
+            status_name(status.expect("a solve without limits finishes"))
+        ),
+    }
+}
+
+/// Outputs the progress of a solve to stderr.
+fn report_progress(progress: &Progress) {
+    let current = progress
+        .current_move
+        .map_or_else(String::new, |player_move| {
+            format!(" (solving {player_move})")
+        });
+
+    let remaining = progress
+        .estimated_remaining()
+        .map_or_else(String::new, |remaining| {
+            format!(", about {:.0} seconds remaining", remaining.as_secs_f64())
+        });
+
+    eprintln!(
+        "Solved {} of {} moves{current}, analysed {} games ({:.0} per second){remaining}",
+        progress.root_moves_completed,
+        progress.root_moves,
+        progress.games,
+        progress.games_per_second()
+    );
+}
+
+/// Solves the position and outputs the result, the final margin, a line of
+/// perfect play, and the outcome of every available move.
+///
+/// With a database, the outcome of each move is looked up in it where possible,
+/// and only the status of each move is output, as margins are not stored.
+fn run_analyse(options: &Options) {
+    let game = options.start(Game::default());
+    let database = options.database.as_deref().map(read_database);
+
+    let now = Instant::now();
+    let mut games = 0;
+    let (status, variation) = match &database {
+        Some(database) => database.principal_variation(game, &mut games),
+        None => solver::principal_variation(game, &mut games),
+    };
+    let analyses: Vec<(Move, Status, Option<i8>)> = match &database {
+        Some(database) => database
+            .move_statuses(game, &mut games)
+            .into_iter()
+            .map(|(player_move, status)| (player_move, status, None))
+            .collect(),
+        None => analysis::analyse(game, &mut games)
+            .into_iter()
+            .map(|analysis| (analysis.player_move, analysis.status, Some(analysis.margin)))
+            .collect(),
+    };
+    let seconds = now.elapsed().as_secs_f64();
+
+    // The moves are ordered from best to worst, so the first move's margin is
+    // the margin for the position.
+    let margin = analyses
+        .first()
+        .map_or_else(|| Some(game.get_margin()), |&(_, _, margin)| margin);
+
+    match options.format {
+        Format::Text => {
+            println!("{}", describe(&game, status));
+
+            if let Some(margin) = margin {
+                println!("Margin for the player to move: {margin}");
+            }
+
+            println!("Perfect play: {}", notation::format_record(&variation));
+
+            if !analyses.is_empty() {
+                println!();
+                println!("Move   Status{}", if margin.is_some() { " Margin" } else { "" });
+
+                for &(player_move, status, margin) in &analyses {
+                    let player_move = player_move.to_string();
+
+                    match margin {
+                        Some(margin) => {
+                            println!("{player_move:<6} {:<6} {margin:>6}", status_name(status))
+                        }
+                        None => println!("{player_move:<6} {}", status_name(status)),
+                    }
+                }
+
+                println!();
+            }
+
+            println!("Analysed {games} games in {seconds:.0} seconds");
+        }
+        Format::Json => println!(
+            "{{\"status\":\"{}\",\"margin\":{},\"variation\":[{}],\"moves\":[{}],\"games\":{games},\"seconds\":{seconds}}}",
+            status_name(status),
+            json_margin(margin),
+            variation
+                .iter()
+                .map(|player_move| format!("\"{player_move}\""))
+                .collect::<Vec<_>>()
+                .join(","),
+            analyses
+                .iter()
+                .map(|&(player_move, status, margin)| format!(
+                    "{{\"move\":\"{player_move}\",\"status\":\"{}\",\"margin\":{}}}",
+                    status_name(status),
+                    json_margin(margin)
+                ))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+/// Counts the positions in the game tree to the specified depth and outputs
+/// the counts.
+fn run_perft(options: &Options, depth: usize) {
+    let game = options.start(Game::initial());
+
+    let now = Instant::now();
+    let perft = perft::perft(game, depth);
+    let seconds = now.elapsed().as_secs_f64();
+
+    match options.format {
+        Format::Text => {
+            println!("Depth Nodes");
+
+            for (depth, nodes) in perft.nodes.iter().enumerate() {
+                println!("{depth:>5} {nodes}");
+            }
+
+            println!();
+            println!("Passes: {}", perft.passes);
+            println!("Games over: {}", perft.games);
+
+            if !perft.margins.is_empty() {
+                println!();
+                println!("Margin Games");
+
+                for (margin, games) in &perft.margins {
+                    println!("{margin:>6} {games}");
+                }
+            }
+
+            println!();
+            println!("Counted in {seconds} seconds");
+        }
+        Format::Json => println!(
+            "{{\"nodes\":{:?},\"passes\":{},\"games\":{},\"margins\":{{{}}},\"seconds\":{seconds}}}",
+            perft.nodes,
+            perft.passes,
+            perft.games,
+            perft
+                .margins
+                .iter()
+                .map(|(margin, games)| format!("\"{margin}\":{games}"))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+/// Solves a set of positions (or the position specified in the options) using
+/// multiple threads and outputs the number of games analysed per second.
+fn run_bench(options: &Options) {
+    let games: Vec<Game> = if options.position.is_some() || options.hands.is_some() {
+        vec![options.start(Game::default())]
+    } else {
+        BENCH_RECORDS
+            .iter()
+            .map(|record| {
+                let moves = notation::parse_record(record).expect("bench records are valid");
+                notation::replay(Game::initial(), &moves).expect("bench records are valid")
+            })
+            .collect()
+    };
+
+    let mut results = Vec::new();
+
+    for game in games {
+        let now = Instant::now();
+        let mut games = 0;
+        parallel::solve_parallel(game, options.threads, &mut games);
+        results.push((game, games, now.elapsed().as_secs_f64()));
+    }
+
+    let total_games: u64 = results.iter().map(|(_, games, _)| games).sum();
+    let total_seconds: f64 = results.iter().map(|(_, _, seconds)| seconds).sum();
+
+    match options.format {
+        Format::Text => {
+            for (game, games, seconds) in &results {
+                println!(
+                    "{game}: {games} games in {seconds:.3} seconds ({:.0} games per second)",
+                    *games as f64 / seconds
+                );
+            }
+
+            println!(
+                "Total: {total_games} games in {total_seconds:.3} seconds ({:.0} games per second) using {} threads",
+                total_games as f64 / total_seconds,
+                options.threads
+            );
+        }
+        Format::Json => println!(
+            "{{\"positions\":[{}],\"games\":{total_games},\"seconds\":{total_seconds},\"threads\":{}}}",
+            results
+                .iter()
+                .map(|(game, games, seconds)| format!(
+                    "{{\"position\":\"{game}\",\"games\":{games},\"seconds\":{seconds}}}"
+                ))
+                .collect::<Vec<_>>()
+                .join(","),
+            options.threads
+        ),
+    }
+}
+
+/// Writes a tablebase of the positions reachable from the position with at most
+/// the specified number of tiles remaining to the specified file, and outputs
+/// the number of positions and the time taken.
+fn run_tablebase(options: &Options, tiles: u8, path: &str) {
+    let now = Instant::now();
+    let tablebase = Tablebase::generate(options.start(Game::default()), tiles, options.threads);
+    let seconds = now.elapsed().as_secs_f64();
+
+    if let Err(error) = File::create(path).and_then(|file| tablebase.write(BufWriter::new(file))) {
+        eprintln!("Error: could not write tablebase: {error}");
+        process::exit(1);
+    }
+
+    match options.format {
+        Format::Text => println!(
+            "Wrote {} positions with at most {tiles} tiles remaining in {seconds:.0} seconds",
+            tablebase.len()
+        ),
+        Format::Json => println!(
+            "{{\"positions\":{},\"tiles\":{tiles},\"seconds\":{seconds}}}",
+            tablebase.len()
+        ),
+    }
+}
+
+/// Checks every position in the specified tablebase file against the solver,
+/// and outputs the first incorrect position, if any, exiting with an error if
+/// there is one.
+fn run_verify_tablebase(options: &Options, path: &str) {
+    let tablebase = read_tablebase(path);
+
+    let now = Instant::now();
+    let mut games = 0;
+    let incorrect = tablebase.verify(&mut games);
+    let seconds = now.elapsed().as_secs_f64();
+
+    match options.format {
+        Format::Text => match incorrect {
+            Some(game) => println!("Incorrect status for position {game}"),
+            None => println!(
+                "Verified {} positions in {seconds:.0} seconds",
+                tablebase.len()
+            ),
+        },
+        Format::Json => println!(
+            "{{\"positions\":{},\"incorrect\":{},\"seconds\":{seconds}}}",
+            tablebase.len(),
+            incorrect.map_or("null".to_string(), |game| format!("\"{game}\""))
+        ),
+    }
+
+    if incorrect.is_some() {
+        process::exit(1);
+    }
+}
+
+/// Returns the tablebase read from the specified file, exiting with an error if
+/// it cannot be read.
+fn read_tablebase(path: &str) -> Tablebase {
+    File::open(path)
+        .map_err(Into::into)
+        .and_then(|file| Tablebase::read(BufReader::new(file)))
+        .unwrap_or_else(|error| {
+            eprintln!("Error: could not read tablebase '{path}': {error}");
+            process::exit(1);
+        })
+}
+
+/// Solves the position, writes every position solved with enough tiles
+/// remaining to the specified database file, and outputs the number of
+/// positions and the time taken.
+fn run_database(options: &Options, path: &str) {
+    let now = Instant::now();
+    let mut games = 0;
+    let database = SolutionDatabase::build(
+        options.start(Game::default()),
+        database::MIN_TILES,
+        &mut games,
+    );
+    let seconds = now.elapsed().as_secs_f64();
+
+    if let Err(error) = File::create(path).and_then(|file| database.write(BufWriter::new(file))) {
+        eprintln!("Error: could not write database: {error}");
+        process::exit(1);
+    }
+
+    match options.format {
+        Format::Text => println!(
+            "Wrote {} positions after analysing {games} games in {seconds:.0} seconds",
+            database.len()
+        ),
+        Format::Json => println!(
+            "{{\"positions\":{},\"games\":{games},\"seconds\":{seconds}}}",
+            database.len()
+        ),
+    }
+}
+
+/// Returns the database read from the specified file, exiting with an error if
+/// it cannot be read.
+fn read_database(path: &str) -> SolutionDatabase<'static> {
+    File::open(path)
+        .map_err(Into::into)
+        .and_then(|file| SolutionDatabase::read(BufReader::new(file)))
+        .unwrap_or_else(|error| {
+            eprintln!("Error: could not read database '{path}': {error}");
+            process::exit(1);
+        })
+}
+
+/// Writes an opening book of the positions reachable from the position in fewer
+/// than the specified number of moves to the specified file, and outputs the
+/// number of positions and the time taken.
+fn run_book(options: &Options, plies: u8, path: &str) {
+    let now = Instant::now();
+    let mut games = 0;
+    let book = OpeningBook::generate(
+        options.start(Game::default()),
+        plies,
+        options.threads,
+        &mut games,
+    );
+    let seconds = now.elapsed().as_secs_f64();
+
+    if let Err(error) = File::create(path).and_then(|file| book.write(BufWriter::new(file))) {
+        eprintln!("Error: could not write opening book: {error}");
+        process::exit(1);
+    }
+
+    match options.format {
+        Format::Text => println!(
+            "Wrote {} positions after analysing {games} games in {seconds:.0} seconds",
+            book.len()
+        ),
+        Format::Json => println!(
+            "{{\"positions\":{},\"games\":{games},\"seconds\":{seconds}}}",
+            book.len()
+        ),
+    }
+}
+
+/// Returns the opening book read from the specified file, exiting with an error
+/// if it cannot be read.
+fn read_book(path: &str) -> OpeningBook {
+    File::open(path)
+        .map_err(Into::into)
+        .and_then(|file| OpeningBook::read(BufReader::new(file)))
+        .unwrap_or_else(|error| {
+            eprintln!("Error: could not read opening book '{path}': {error}");
+            process::exit(1);
+        })
+}
+
+/// Returns a sentence describing the victory status of the specified position.
+fn describe(game: &Game, status: Status) -> String {
+    if *game == Game::default() || *game == Game::initial() {
+        format!(
+            "Mijnlieff is a {} with perfect play",
+            match status {
+                Status::Win => "win for the first player",
+                Status::Draw => "draw",
+                Status::Loss => "win for the second player",
+            }
+        )
+    } else {
+        format!(
+            "The position is a {} for the player to move with perfect play",
+            status_name(status)
+        )
+    }
+}
+
+/// Returns the name of the specified victory status.
+fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Win => "win",
+        Status::Draw => "draw",
+        Status::Loss => "loss",
+    }
+}
+
+/// Returns the JSON value for the specified margin, which is `null` if it is
+/// not known.
+fn json_margin(margin: Option<i8>) -> String {
+    margin.map_or("null".to_string(), |margin| margin.to_string())
+}
@@ -0,0 +1,306 @@
+//! Provides a function for playing against the solver in the terminal.
+
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+use mijnlieff_solver::solver;
+use mijnlieff_solver::{Board, Game, Hand, Move, OpeningBook, SolutionDatabase, Tile};
+
+/// The help message output during play.
+const HELP: &str = "Enter a move as a tile letter (L for Puller, P for Pusher, S for Straight,
+or D for Diagonal) followed by a square from a1 to d4, such as 'P b2'.
+Enter 'moves' to list the legal moves, or 'quit' to stop playing.";
+
+/// Plays a game against the solver from the specified position, reading the
+/// user's moves from the input and writing the board and the solver's moves to
+/// the output.
+///
+/// If an opening book or a database is specified, the solver's moves are
+/// looked up in them (trying the book first), and only found by searching for
+/// positions that are not stored.
+pub fn play(
+    game: Game,
+    user_first: bool,
+    book: Option<&OpeningBook>,
+    database: Option<&SolutionDatabase>,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    writeln!(output, "{HELP}")?;
+
+    let mut game = game;
+    let mut user_to_move = user_first;
+
+    while !game.is_over() {
+        if user_to_move {
+            writeln!(output)?;
+            write_game(&mut output, &game)?;
+        }
+
+        if game.player_must_pass() {
+            let player = if user_to_move { "You" } else { "The computer" };
+            writeln!(output, "{player} must pass")?;
+            game = game.with_pass();
+            user_to_move = !user_to_move;
+            continue;
+        }
+
+        if user_to_move {
+            write!(output, "Your move: ")?;
+            output.flush()?;
+
+            let mut line = String::new();
+
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            match line.trim() {
+                "" => continue,
+                "quit" => return Ok(()),
+                "help" => writeln!(output, "{HELP}")?,
+                "moves" => {
+                    let moves: Vec<String> = game
+                        .legal_moves()
+                        .map(|player_move| player_move.to_string())
+                        .collect();
+                    writeln!(output, "Legal moves: {}", moves.join(", "))?;
+                }
+                line => match line.parse::<Move>() {
+                    Ok(player_move) => match game.try_after(player_move) {
+                        Ok(next) => {
+                            game = next;
+                            user_to_move = false;
+                        }
+                        Err(error) => writeln!(output, "Illegal move: {error}")?,
+                    },
+                    Err(error) => {
+                        writeln!(output, "Invalid move: {error} (enter 'help' for help)")?
+                    }
+                },
+            }
+        } else {
+            writeln!(output, "The computer is thinking...")?;
+
+            // Every first move is a rotation or reflection of a move allowed
+            // from the default position, which is much quicker to solve.
+            let search_game = if game == Game::initial() {
+                Game::default()
+            } else {
+                game
+            };
+
+            let book_move = book
+                .and_then(|book| book.get(&search_game))
+                .and_then(|(_, moves)| moves.first().copied());
+
+            let player_move = match (book_move, database) {
+                (Some(player_move), _) => Some(player_move),
+                (None, Some(database)) => database.best_move(search_game, &mut 0),
+                (None, None) => solver::best_move(search_game, &mut 0),
+            }
+            .expect("the game is not over");
+            writeln!(output, "The computer plays {player_move}")?;
+
+            game = game.after(player_move);
+            user_to_move = true;
+        }
+    }
+
+    // The game is over, so the current player's board holds their final score.
+    let (user_board, computer_board) = if user_to_move {
+        (game.get_board(), game.get_opponent_board())
+    } else {
+        (game.get_opponent_board(), game.get_board())
+    };
+
+    let user_score = user_board.get_score();
+    let computer_score = computer_board.get_score();
+
+    writeln!(output)?;
+    writeln!(
+        output,
+        "Final score: you {user_score}, the computer {computer_score}"
+    )?;
+    writeln!(
+        output,
+        "{}",
+        match user_score.cmp(&computer_score) {
+            Ordering::Greater => "You win!",
+            Ordering::Equal => "The game is drawn",
+            Ordering::Less => "The computer wins",
+        }
+    )?;
+
+    Ok(())
+}
+
+/// Writes the user's squares, the computer's squares, and the squares available
+/// to the user side by side, followed by both players' hands.
+fn write_game(output: &mut impl Write, game: &Game) -> io::Result<()> {
+    let [user, computer, unavailable] = [
+        game.get_board(),
+        game.get_opponent_board(),
+        game.get_unavailable(),
+    ]
+    .map(|board: Board| board.to_string());
+
+    writeln!(output, "  You       Computer  Available")?;
+
+    for (row, ((user, computer), unavailable)) in user
+        .lines()
+        .zip(computer.lines())
+        .zip(unavailable.lines())
+        .enumerate()
+    {
+        writeln!(output, "{} {user}   {computer}   {unavailable}", row + 1)?;
+    }
+
+    writeln!(output, "  a b c d   a b c d   a b c d")?;
+    writeln!(output, "Your hand: {}", format_hand(game.get_hand()))?;
+    writeln!(
+        output,
+        "Computer's hand: {}",
+        format_hand(game.get_opponent_hand())
+    )
+}
+
+/// Returns a description of the tiles in the specified hand.
+fn format_hand(hand: Hand) -> String {
+    Tile::ALL
+        .iter()
+        .map(|&tile| format!("{} {tile}", hand.count(tile)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mijnlieff_solver::fixtures::create_small_game;
+    use mijnlieff_solver::Status;
+
+    /// Plays the specified game with the specified input, returning the
+    /// output.
+    fn play_str(
+        game: Game,
+        user_first: bool,
+        book: Option<&OpeningBook>,
+        database: Option<&SolutionDatabase>,
+        input: &str,
+    ) -> String {
+        let mut output = Vec::new();
+        play(
+            game,
+            user_first,
+            book,
+            database,
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Returns a legal move from the specified game that is not the move found
+    /// by searching, so that the tests can tell whether it was looked up.
+    fn unsearched_move(game: Game) -> Move {
+        let best_move = solver::best_move(game, &mut 0);
+        game.legal_moves()
+            .find(|&player_move| Some(player_move) != best_move)
+            .unwrap()
+    }
+
+    #[test]
+    fn play_commands() {
+        let game = create_small_game();
+        let output = play_str(game, true, None, None, "help\n\nmoves\nX z9\nquit\n");
+
+        assert_eq!(2, output.matches(HELP).count());
+        assert!(output.contains("  You       Computer  Available\n"));
+        assert!(output.contains("Your hand: 1 Puller, 1 Pusher, 0 Straight, 0 Diagonal\n"));
+        assert!(output.contains("Computer's hand: 0 Puller, 0 Pusher, 1 Straight, 1 Diagonal\n"));
+
+        let moves: Vec<String> = game.legal_moves().map(|m| m.to_string()).collect();
+        assert!(output.contains(&format!("Legal moves: {}\n", moves.join(", "))));
+        assert!(output.contains("Invalid move: "));
+        assert_eq!(5, output.matches("Your move: ").count());
+        assert!(!output.contains("The computer is thinking..."));
+        assert!(!output.contains("Final score"));
+
+        // The end of the input stops the game.
+        let output = play_str(game, true, None, None, "");
+        assert_eq!(1, output.matches("Your move: ").count());
+    }
+
+    #[test]
+    fn play_game() {
+        let game = create_small_game();
+
+        // Try every move with each tile, so the legal ones are eventually
+        // played whatever the computer replies.
+        let input: String = ["L", "P"]
+            .iter()
+            .flat_map(|tile| {
+                ["a", "b", "c", "d"].iter().flat_map(move |column| {
+                    (1..=4).map(move |row| format!("{tile} {column}{row}\n"))
+                })
+            })
+            .collect();
+
+        let output = play_str(game, true, None, None, &input);
+        assert!(output.contains("Illegal move: "));
+        assert_eq!(2, output.matches("The computer plays ").count());
+
+        let result = output.lines().last().unwrap();
+        assert!(["You win!", "The game is drawn", "The computer wins"].contains(&result));
+        assert!(output.contains("Final score: you "));
+    }
+
+    #[test]
+    fn play_with_book() {
+        let game = create_small_game();
+        let player_move = unsearched_move(game);
+
+        // The book is written and read back, as the command does.
+        let book = OpeningBook::generate(game, 1, 1, &mut 0).with_entry(
+            &game,
+            Status::Draw,
+            &[player_move],
+        );
+        let mut bytes = Vec::new();
+        book.write(&mut bytes).unwrap();
+        let book = OpeningBook::read(bytes.as_slice()).unwrap();
+
+        let output = play_str(game, false, Some(&book), None, "");
+        assert!(output.contains(&format!("The computer plays {player_move}\n")));
+
+        // Positions not in the book are searched.
+        let output = play_str(game.with_pass(), false, Some(&book), None, "");
+        let best_move = solver::best_move(game.with_pass(), &mut 0).unwrap();
+        assert!(output.contains(&format!("The computer plays {best_move}\n")));
+    }
+
+    #[test]
+    fn play_with_database() {
+        let game = create_small_game();
+        let player_move = unsearched_move(game);
+
+        // The database is written and read back, as the command does.
+        let database =
+            SolutionDatabase::build(game, 0, &mut 0).with_entry(&game, Status::Draw, player_move);
+        let mut bytes = Vec::new();
+        database.write(&mut bytes).unwrap();
+        let database = SolutionDatabase::read(bytes.as_slice()).unwrap();
+
+        let output = play_str(game, false, None, Some(&database), "");
+        assert!(output.contains(&format!("The computer plays {player_move}\n")));
+
+        // The book is tried before the database.
+        let book = OpeningBook::generate(game, 1, 1, &mut 0);
+        let book_move = book.get(&game).unwrap().1[0];
+        assert_ne!(player_move, book_move);
+        let output = play_str(game, false, Some(&book), Some(&database), "");
+        assert!(output.contains(&format!("The computer plays {book_move}\n")));
+    }
+}
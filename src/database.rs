@@ -0,0 +1,525 @@
+//! Defines a struct representing a database of solved positions.
+
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::io::{self, Read, Write};
+
+use crate::board::Board;
+use crate::format::{self, FileError, FileKind, Header};
+use crate::game::Game;
+use crate::moves::Move;
+use crate::solver::{self, SolveHook};
+use crate::status::Status;
+use crate::table::TranspositionTable;
+
+/// Describes a database file.
+const KIND: FileKind = FileKind {
+    name: "a database",
+    magic: *b"MLSD",
+    version: 1,
+};
+
+/// The number of bytes in each entry of a database file.
+const ENTRY_BYTES: usize = 16;
+
+/// The minimum number of tiles remaining in both players' hands for a position
+/// to be stored when building a database for the whole game.
+///
+/// Positions closer to the end of the game are quicker to solve than to store.
+pub const MIN_TILES: u8 = 7;
+
+/// The number of bytes used by the transposition table caching the statuses of
+/// the positions solved while building a database.
+const TABLE_BYTES: usize = 1 << 26;
+
+/// Represents a database of solved positions, storing the victory status of
+/// each position and a move achieving it.
+///
+/// Positions are stored under their canonical instance, as each entry's key
+/// (as returned by `Game::key`) shifted left by nine bits, with a move code in
+/// the next seven bits and the status in the two low bits. The entries are
+/// sorted, so a position is looked up with a binary search.
+///
+/// A database is written to a file as a 32 byte header, in the format
+/// described in the `format` module, followed by each entry as a little-endian
+/// `u128`. The header contains the bytes `MLSD`, a zero byte, and these
+/// fields:
+///
+/// 1. A fingerprint of the rules compiled into `Tile::MOVES` and
+///    `Board::LINES`, so that a database built under different rules is
+///    rejected.
+/// 2. The number of entries.
+/// 3. A 64 bit FNV-1a checksum of the entries.
+///
+/// As the entries have a fixed size, they are searched in place in the bytes
+/// of the file, so a memory-mapped file can be used with
+/// `SolutionDatabase::from_bytes` without copying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionDatabase<'a> {
+    /// The bytes of the sorted entries, each containing a position's key, move,
+    /// and status.
+    entries: Cow<'a, [u8]>,
+}
+
+impl SolutionDatabase<'static> {
+    /// Returns the database of every position solved in solving the specified
+    /// position, except those with fewer than the specified number of tiles
+    /// remaining in both players' hands.
+    ///
+    /// Each position is solved at most once, as the positions already stored
+    /// act as a transposition table. Positions are only stored once they have
+    /// been solved, so positions after moves that did not need to be analysed
+    /// (because an earlier move wins) are not stored.
+    ///
+    /// The third parameter is updated with a count of the number games
+    /// analysed.
+    pub fn build(game: Game, min_tiles: u8, games: &mut u64) -> Self {
+        let mut recorder = Recorder {
+            min_tiles,
+            table: TranspositionTable::new(TABLE_BYTES),
+            entries: Vec::new(),
+        };
+        solver::solve_hooked(game, games, &mut recorder).expect("the solve has no limits");
+
+        let mut entries = recorder.entries;
+
+        // A position is recorded again if its status has been replaced in the
+        // table, or if a symmetric instance is reached by another path, and
+        // the move recorded may differ. Any of the entries for a position is
+        // correct, as each has its status and a move achieving it, so only the
+        // first is kept.
+        entries.sort_unstable();
+        entries.dedup_by_key(|entry| *entry >> 9);
+
+        SolutionDatabase {
+            entries: entries
+                .into_iter()
+                .flat_map(|entry| entry.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    /// Reads a database in the format described for `SolutionDatabase`,
+    /// checking it as `from_bytes` does.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, FileError> {
+        let header = Header::read(&KIND, &mut reader)?;
+
+        let mut entries = Vec::new();
+        reader.read_to_end(&mut entries)?;
+
+        SolutionDatabase::check(header, &entries)?;
+        Ok(SolutionDatabase {
+            entries: Cow::Owned(entries),
+        })
+    }
+}
+
+impl<'a> SolutionDatabase<'a> {
+    /// Returns the database in the specified bytes, in the format described for
+    /// `SolutionDatabase`, without copying the entries.
+    ///
+    /// The header is checked to be valid and to match the compiled rules, and
+    /// the entries are checked to match the checksum, and to be sorted and
+    /// valid.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FileError> {
+        let header = Header::parse(&KIND, bytes)?;
+        let entries = &bytes[Header::<3>::BYTES..];
+
+        SolutionDatabase::check(header, entries)?;
+        Ok(SolutionDatabase {
+            entries: Cow::Borrowed(entries),
+        })
+    }
+
+    /// Returns the number of positions stored.
+    pub fn len(&self) -> usize {
+        self.entries.len() / ENTRY_BYTES
+    }
+
+    /// Returns whether no positions are stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the victory status of the specified position and a move
+    /// achieving it, or `None` if the position is not stored.
+    pub fn get(&self, game: &Game) -> Option<(Status, Move)> {
+        let symmetry = game.canonical_symmetry();
+        let key = game.transform(symmetry).key();
+
+        // The entries are searched in place, so the binary search is over
+        // their indices.
+        let (mut low, mut high) = (0, self.len());
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            if record(&self.entries, middle) >> 9 < key {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let entry = (low < self.len())
+            .then(|| record(&self.entries, low))
+            .filter(|&entry| entry >> 9 == key)?;
+
+        let status = decode_status(entry).expect("entries are valid");
+        let player_move = decode_move(entry).expect("entries are valid");
+
+        // The move is stored for the canonical instance, so is transformed
+        // back.
+        Some((
+            status,
+            player_move.transform(Board::inverse_symmetry(symmetry)),
+        ))
+    }
+
+    /// Returns a move achieving the best possible status from the specified
+    /// position, looking it up if the position is stored and otherwise finding
+    /// it with `solver::best_move`.
+    ///
+    /// The second parameter is updated with a count of the number games
+    /// analysed.
+    pub fn best_move(&self, game: Game, games: &mut u64) -> Option<Move> {
+        match self.get(&game) {
+            Some((_, player_move)) => Some(player_move),
+            None => solver::best_move(game, games),
+        }
+    }
+
+    /// Returns the victory status of each move available from the specified
+    /// position, from the perspective of the player making the move, ordered
+    /// from best to worst and then in order of preference.
+    ///
+    /// The status after each move is looked up if the position is stored and
+    /// otherwise found with `solver::solve`. If the game is over, no moves are
+    /// returned.
+    ///
+    /// The second parameter is updated with a count of the number games
+    /// analysed.
+    pub fn move_statuses(&self, game: Game, games: &mut u64) -> Vec<(Move, Status)> {
+        let mut statuses: Vec<(Move, Status)> = solver::get_moves(&game)
+            .into_iter()
+            .map(|player_move| {
+                let after = game.after(player_move);
+                let status = match self.get(&after) {
+                    Some((status, _)) => status,
+                    None => solver::solve(after, games),
+                };
+
+                (player_move, !status)
+            })
+            .collect();
+
+        // The sort is stable, so moves with the same status stay in order of
+        // preference.
+        statuses.sort_by_key(|&(_, status)| Reverse(format::encode_status(status)));
+        statuses
+    }
+
+    /// Returns the victory status and a line of perfect play from the specified
+    /// position until the end of the game, looking up each position that is
+    /// stored and continuing with `solver::principal_variation` from the first
+    /// position that is not.
+    ///
+    /// The second parameter is updated with a count of the number games
+    /// analysed.
+    pub fn principal_variation(&self, game: Game, games: &mut u64) -> (Status, Vec<Move>) {
+        let Some((status, _)) = self.get(&game) else {
+            return solver::principal_variation(game, games);
+        };
+
+        let mut variation = Vec::new();
+        let mut game = game;
+
+        while let Some((_, player_move)) = self.get(&game) {
+            variation.push(player_move);
+            game = game.after(player_move);
+        }
+
+        variation.extend(solver::principal_variation(game, games).1);
+        (status, variation)
+    }
+
+    /// Returns a copy of the database storing the specified status and move for
+    /// the specified position, replacing any entry already stored for it.
+    ///
+    /// This lets tests store a move that `build` would not choose, so that
+    /// they can tell whether a move was looked up.
+    #[doc(hidden)]
+    pub fn with_entry(
+        &self,
+        game: &Game,
+        status: Status,
+        player_move: Move,
+    ) -> SolutionDatabase<'static> {
+        let entry = encode_entry(game, status, player_move);
+
+        let mut entries: Vec<u128> = records(&self.entries)
+            .filter(|&other| other >> 9 != entry >> 9)
+            .collect();
+        entries.insert(entries.partition_point(|&other| other < entry), entry);
+
+        SolutionDatabase {
+            entries: entries
+                .into_iter()
+                .flat_map(|entry| entry.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    /// Writes the database in the format described for `SolutionDatabase`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let header = Header {
+            parameter: 0,
+            fields: [
+                format::rules_fingerprint(),
+                self.len() as u64,
+                format::fnv1a(self.entries.iter().copied()),
+            ],
+        };
+        header.write(&KIND, &mut writer)?;
+        writer.write_all(&self.entries)?;
+        writer.flush()
+    }
+
+    /// Checks that the specified header is valid and matches the compiled
+    /// rules, and that the specified bytes of the entries match the checksum,
+    /// and are sorted and valid.
+    fn check(header: Header<3>, entries: &[u8]) -> Result<(), FileError> {
+        let invalid = FileError::InvalidFormat;
+        let [fingerprint, count, expected_checksum] = header.fields;
+
+        if fingerprint != format::rules_fingerprint() {
+            return Err(FileError::RulesMismatch);
+        }
+
+        if entries.len() as u64 != count * ENTRY_BYTES as u64 {
+            return Err(invalid(format!(
+                "expected {count} entries, found {} bytes",
+                entries.len()
+            )));
+        }
+
+        if format::fnv1a(entries.iter().copied()) != expected_checksum {
+            return Err(FileError::ChecksumMismatch);
+        }
+
+        if records(entries)
+            .zip(records(entries).skip(1))
+            .any(|(entry, next)| entry >> 9 >= next >> 9)
+        {
+            return Err(invalid("the entries are not sorted".to_string()));
+        }
+
+        if records(entries)
+            .any(|entry| decode_status(entry).is_none() || decode_move(entry).is_none())
+        {
+            return Err(invalid(
+                "an entry has an invalid status or move".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Records the positions solved in building a database, as a hook for
+/// `solver::solve_hooked`.
+struct Recorder {
+    /// The minimum number of tiles remaining for a position to be recorded.
+    min_tiles: u8,
+
+    /// The statuses of the canonical positions recorded, so that each is only
+    /// solved once unless it is replaced in the table.
+    table: TranspositionTable,
+
+    /// The entries recorded, in the order they were solved.
+    entries: Vec<u128>,
+}
+
+impl SolveHook for Recorder {
+    fn lookup(&mut self, game: &Game, games: &mut u64) -> Option<Status> {
+        if game.tiles_remaining() < self.min_tiles {
+            return Some(solver::solve(*game, games));
+        }
+
+        self.table.get(&game.canonical())
+    }
+
+    fn record(&mut self, game: &Game, status: Status, player_move: Move) {
+        self.table.insert(game.canonical(), status);
+        self.entries.push(encode_entry(game, status, player_move));
+    }
+}
+
+/// Returns the entry storing the specified status and move for the canonical
+/// instance of the specified position.
+fn encode_entry(game: &Game, status: Status, player_move: Move) -> u128 {
+    // The move is stored for the canonical instance, so is transformed to
+    // match it.
+    let symmetry = game.canonical_symmetry();
+
+    game.transform(symmetry).key() << 9
+        | u128::from(format::encode_move(player_move.transform(symmetry))) << 2
+        | u128::from(format::encode_status(status))
+}
+
+/// Returns the little-endian entries in the specified bytes, which are a
+/// multiple of the size of an entry.
+fn records(entries: &[u8]) -> impl Iterator<Item = u128> + '_ {
+    entries
+        .chunks_exact(ENTRY_BYTES)
+        .map(|bytes| u128::from_le_bytes(bytes.try_into().expect("16 bytes")))
+}
+
+/// Returns the little-endian entry with the specified index in the specified
+/// bytes.
+fn record(entries: &[u8], index: usize) -> u128 {
+    let bytes = &entries[index * ENTRY_BYTES..(index + 1) * ENTRY_BYTES];
+    u128::from_le_bytes(bytes.try_into().expect("16 bytes"))
+}
+
+/// Returns the status represented by the two low bits of the specified entry,
+/// or `None` if they do not represent a status.
+fn decode_status(entry: u128) -> Option<Status> {
+    format::decode_status((entry & 0b11) as u8)
+}
+
+/// Returns the move represented by the move code in the specified entry, or
+/// `None` if it does not represent a move.
+fn decode_move(entry: u128) -> Option<Move> {
+    format::decode_move((entry >> 2 & 0b111_1111) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::create_small_game;
+    use crate::tile::Tile;
+
+    #[test]
+    fn build() {
+        let game = create_small_game();
+        let mut games = 0;
+        let database = SolutionDatabase::build(game, 0, &mut games);
+
+        let (status, player_move) = database.get(&game).unwrap();
+        assert_eq!(solver::solve(game, &mut games), status);
+        assert_eq!(!status, solver::solve(game.after(player_move), &mut games));
+
+        // Positions with fewer tiles remaining are not stored.
+        let database = SolutionDatabase::build(game, 4, &mut games);
+        assert_eq!(1, database.len());
+    }
+
+    #[test]
+    fn get() {
+        let game = create_small_game();
+        let database = SolutionDatabase::build(game, 0, &mut 0);
+
+        // The position after the best move has been solved, and the move stored
+        // for each symmetric instance is legal and achieves the stored status.
+        let (_, player_move) = database.get(&game).unwrap();
+        let game = game.after(player_move);
+
+        for symmetry in 0..8 {
+            let transformed = game.transform(symmetry);
+            let (status, player_move) = database.get(&transformed).unwrap();
+            let after = transformed.try_after(player_move).unwrap();
+            assert_eq!(!status, solver::solve(after, &mut 0));
+        }
+
+        assert_eq!(None, database.get(&Game::default()));
+    }
+
+    #[test]
+    fn with_entry() {
+        let game = create_small_game().after(Move::Play(Tile::Pusher, 1));
+        let database = SolutionDatabase::build(create_small_game(), 0, &mut 0);
+        let player_move = Move::Play(Tile::Straight, 15);
+        let changed = database.with_entry(&game, Status::Draw, player_move);
+
+        assert_eq!(database.len(), changed.len());
+        assert_eq!(Some((Status::Draw, player_move)), changed.get(&game));
+
+        // The entries stay sorted, so a new position can be found.
+        let added = changed.with_entry(&Game::default(), Status::Win, Move::Pass);
+        assert_eq!(database.len() + 1, added.len());
+        assert_eq!(Some((Status::Win, Move::Pass)), added.get(&Game::default()));
+        assert_eq!(changed.get(&game), added.get(&game));
+    }
+
+    #[test]
+    fn move_statuses() {
+        let game = create_small_game();
+        let database = SolutionDatabase::build(game, 2, &mut 0);
+        let statuses = database.move_statuses(game, &mut 0);
+
+        assert_eq!(solver::get_moves(&game).len(), statuses.len());
+        assert_eq!(solver::solve(game, &mut 0), statuses[0].1);
+
+        for (player_move, status) in statuses {
+            assert_eq!(!status, solver::solve(game.after(player_move), &mut 0));
+        }
+    }
+
+    #[test]
+    fn principal_variation() {
+        let game = create_small_game();
+        let database = SolutionDatabase::build(game, 2, &mut 0);
+
+        let (status, variation) = database.principal_variation(game, &mut 0);
+        assert_eq!(solver::solve(game, &mut 0), status);
+        assert_eq!(
+            4,
+            variation
+                .iter()
+                .filter(|&&player_move| player_move != Move::Pass)
+                .count()
+        );
+    }
+
+    #[test]
+    fn write_and_read() {
+        let database = SolutionDatabase::build(create_small_game(), 2, &mut 0);
+
+        let mut bytes = Vec::new();
+        database.write(&mut bytes).unwrap();
+        assert_eq!(
+            Header::<3>::BYTES + database.len() * ENTRY_BYTES,
+            bytes.len()
+        );
+        assert_eq!(database, SolutionDatabase::read(bytes.as_slice()).unwrap());
+        assert_eq!(database, SolutionDatabase::from_bytes(&bytes).unwrap());
+
+        let mut corrupted = bytes.clone();
+        corrupted[Header::<3>::BYTES + 8] ^= 1;
+        assert!(matches!(
+            SolutionDatabase::read(corrupted.as_slice()),
+            Err(FileError::ChecksumMismatch)
+        ));
+
+        let mut other_rules = bytes.clone();
+        other_rules[8] ^= 1;
+        assert!(matches!(
+            SolutionDatabase::read(other_rules.as_slice()),
+            Err(FileError::RulesMismatch)
+        ));
+
+        assert!(matches!(
+            SolutionDatabase::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FileError::InvalidFormat(_))
+        ));
+
+        bytes[4] = 2;
+        assert_eq!(
+            "invalid format: unsupported version 2",
+            SolutionDatabase::read(bytes.as_slice())
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Defines the positions shared by the tests of several modules and of the
+//! command line interface.
+
+use crate::game::Game;
+use crate::hand::Hand;
+use crate::tile::Tile;
+
+// Creates the following board arrangement, with every unoccupied square
+// available for the next move:
+//
+// 1 2 1 2
+// 2 1 2 1
+// 1 2 . .
+// 1 2 . .
+//
+// The specified tiles are used, so the solver can be tested with the
+// remaining tiles. Note that this function takes advantage of the fact that
+// the validity of moves isn't checked, and it makes mostly invalid moves.
+pub fn create_game(tile_1: Tile, tile_2: Tile, tile_3: Tile) -> Game {
+    Game::default()
+        .with_move(tile_1, 0)
+        .with_move(tile_1, 1)
+        .with_move(tile_1, 2)
+        .with_move(tile_1, 3)
+        .with_move(tile_2, 7)
+        .with_move(tile_2, 6)
+        .with_move(tile_2, 5)
+        .with_move(tile_2, 4)
+        .with_move(tile_3, 8)
+        .with_move(tile_3, 9)
+        .with_move(tile_3, 12)
+        .with_move(tile_3, 13)
+        .with_pass()
+        .with_pass()
+}
+
+// Creates a position on an empty board with two tiles in each hand, so that
+// the whole game is quick to solve and fits in a small file.
+pub fn create_small_game() -> Game {
+    Game::initial().with_hands(Hand::new([1, 1, 0, 0]), Hand::new([0, 0, 1, 1]))
+}
@@ -0,0 +1,266 @@
+//! Defines the parts of the file formats shared by tablebases, solution
+//! databases, and opening books.
+//!
+//! Each file starts with a header containing four bytes identifying the kind
+//! of file, the format version as a little-endian `u16`, a byte whose meaning
+//! depends on the kind of file, a reserved zero byte, and a number of
+//! little-endian `u64` fields that depends on the kind of file.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, Read, Write};
+
+use crate::board::Board;
+use crate::moves::Move;
+use crate::status::Status::{self, *};
+use crate::tile::Tile;
+
+/// The code representing a pass. Other moves are represented by the tile
+/// discriminant multiplied by 16 plus the square.
+pub(crate) const PASS_CODE: u8 = 64;
+
+/// Describes a kind of file.
+pub(crate) struct FileKind {
+    /// The name of the kind of file, used in error messages.
+    pub(crate) name: &'static str,
+
+    /// The bytes identifying the kind of file.
+    pub(crate) magic: [u8; 4],
+
+    /// The version of the file format.
+    pub(crate) version: u16,
+}
+
+/// Represents the header of a file with the specified number of `u64` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Header<const FIELDS: usize> {
+    /// The byte whose meaning depends on the kind of file.
+    pub(crate) parameter: u8,
+
+    /// The fields.
+    pub(crate) fields: [u64; FIELDS],
+}
+
+impl<const FIELDS: usize> Header<FIELDS> {
+    /// The number of bytes in the header.
+    pub(crate) const BYTES: usize = 8 + 8 * FIELDS;
+
+    /// Writes the header for the specified kind of file.
+    pub(crate) fn write<W: Write>(&self, kind: &FileKind, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&kind.magic)?;
+        writer.write_all(&kind.version.to_le_bytes())?;
+        writer.write_all(&[self.parameter, 0])?;
+
+        for field in self.fields {
+            writer.write_all(&field.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the header of the specified kind of file, checking the bytes
+    /// identifying it and the format version.
+    pub(crate) fn read<R: Read>(kind: &FileKind, reader: &mut R) -> Result<Self, FileError> {
+        let mut bytes = vec![0; Self::BYTES];
+        reader.read_exact(&mut bytes)?;
+        Self::parse(kind, &bytes)
+    }
+
+    /// Returns the header at the start of the specified bytes, checking the
+    /// bytes identifying the kind of file and the format version.
+    pub(crate) fn parse(kind: &FileKind, bytes: &[u8]) -> Result<Self, FileError> {
+        let invalid = FileError::InvalidFormat;
+
+        let bytes = bytes
+            .get(..Self::BYTES)
+            .ok_or_else(|| invalid("the header is truncated".to_string()))?;
+
+        if bytes[0..4] != kind.magic {
+            return Err(invalid(format!("not {} file", kind.name)));
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+
+        if version != kind.version {
+            return Err(invalid(format!("unsupported version {version}")));
+        }
+
+        let mut fields = [0; FIELDS];
+
+        for (field, bytes) in fields.iter_mut().zip(bytes[8..].chunks_exact(8)) {
+            *field = u64::from_le_bytes(bytes.try_into().expect("8 bytes"));
+        }
+
+        Ok(Header {
+            parameter: bytes[6],
+            fields,
+        })
+    }
+}
+
+/// Represents an error in reading a file.
+#[derive(Debug)]
+pub enum FileError {
+    /// The file could not be read.
+    Io(io::Error),
+
+    /// The file was not in the correct format. The field contains a
+    /// description of the problem.
+    InvalidFormat(String),
+
+    /// The file was written under different rules from those compiled into
+    /// `Tile::MOVES` and `Board::LINES`.
+    RulesMismatch,
+
+    /// The checksum of the file's contents did not match the checksum in the
+    /// header.
+    ChecksumMismatch,
+}
+
+impl Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(error) => write!(f, "{error}"),
+            FileError::InvalidFormat(problem) => write!(f, "invalid format: {problem}"),
+            FileError::RulesMismatch => f.write_str("it was written under different rules"),
+            FileError::ChecksumMismatch => f.write_str("incorrect checksum"),
+        }
+    }
+}
+
+impl Error for FileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FileError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FileError {
+    fn from(error: io::Error) -> Self {
+        FileError::Io(error)
+    }
+}
+
+/// Returns a fingerprint of the rules compiled into `Tile::MOVES` and
+/// `Board::LINES`, so that a file written under different rules is rejected.
+pub(crate) fn rules_fingerprint() -> u64 {
+    let moves = Tile::ALL
+        .into_iter()
+        .flat_map(|tile| (0..16).map(move |square| u16::from(tile.get_unavailable(square))));
+
+    fnv1a(
+        moves
+            .chain(Board::LINES)
+            .flat_map(|bits| bits.to_le_bytes()),
+    )
+}
+
+/// Returns the 64 bit FNV-1a hash of the specified bytes.
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns the two bits representing the specified status, which increase as
+/// the status improves.
+pub(crate) fn encode_status(status: Status) -> u8 {
+    match status {
+        Loss => 0,
+        Draw => 1,
+        Win => 2,
+    }
+}
+
+/// Returns the status represented by the specified two bits, or `None` if they
+/// do not represent a status.
+pub(crate) fn decode_status(code: u8) -> Option<Status> {
+    match code {
+        0 => Some(Loss),
+        1 => Some(Draw),
+        2 => Some(Win),
+        _ => None,
+    }
+}
+
+/// Returns the seven bits representing the specified move.
+pub(crate) fn encode_move(player_move: Move) -> u8 {
+    match player_move {
+        Move::Play(tile, square) => tile as u8 * 16 + square,
+        Move::Pass => PASS_CODE,
+    }
+}
+
+/// Returns the move represented by the specified seven bits, or `None` if
+/// they do not represent a move.
+pub(crate) fn decode_move(code: u8) -> Option<Move> {
+    match code {
+        PASS_CODE => Some(Move::Pass),
+        code if code < PASS_CODE => Some(Move::Play(Tile::ALL[code as usize / 16], code % 16)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIND: FileKind = FileKind {
+        name: "a test",
+        magic: *b"TEST",
+        version: 3,
+    };
+
+    #[test]
+    fn header() {
+        let header = Header {
+            parameter: 7,
+            fields: [1, u64::MAX],
+        };
+
+        let mut bytes = Vec::new();
+        header.write(&KIND, &mut bytes).unwrap();
+        assert_eq!(Header::<2>::BYTES, bytes.len());
+        assert_eq!(header, Header::read(&KIND, &mut bytes.as_slice()).unwrap());
+
+        assert_eq!(
+            "invalid format: the header is truncated",
+            Header::<2>::parse(&KIND, &bytes[1..])
+                .unwrap_err()
+                .to_string()
+        );
+
+        bytes[4] = 2;
+        assert_eq!(
+            "invalid format: unsupported version 2",
+            Header::<2>::parse(&KIND, &bytes).unwrap_err().to_string()
+        );
+
+        bytes[0] = b'X';
+        assert_eq!(
+            "invalid format: not a test file",
+            Header::<2>::parse(&KIND, &bytes).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        for status in [Loss, Draw, Win] {
+            assert_eq!(Some(status), decode_status(encode_status(status)));
+        }
+
+        assert_eq!(None, decode_status(3));
+
+        for tile in Tile::ALL {
+            for square in 0..16 {
+                let player_move = Move::Play(tile, square);
+                assert_eq!(Some(player_move), decode_move(encode_move(player_move)));
+            }
+        }
+
+        assert_eq!(Some(Move::Pass), decode_move(encode_move(Move::Pass)));
+        assert_eq!(None, decode_move(PASS_CODE + 1));
+    }
+}
@@ -1,7 +1,11 @@
 //! Defines a struct representing the game state.
 
+use std::error::Error;
+use std::fmt::{self, Display};
+
 use crate::board::Board;
 use crate::hand::Hand;
+use crate::moves::{LegalMoves, Move};
 use crate::status::Status;
 use crate::tile::Tile;
 
@@ -12,11 +16,16 @@ use crate::tile::Tile;
 /// in these squares.
 const INITIAL_UNAVAILABLE: u16 = 0b_1111_1111_1111_1100;
 
+/// A bit field representing the squares unavailable for the first move under
+/// the rules, which are the central squares.
+const START_UNAVAILABLE: u16 = 0b_0000_0110_0110_0000;
+
 /// Represents the game state.
 ///
 /// Only the state necessary to solve the game is stored: specifically, the
 /// board of squares occupied by each player, each player's hand, and the board
 /// of squares unavailable due to the previous player's move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Game {
     /// The board of squares occupied by the current player.
     board: Board,
@@ -34,6 +43,48 @@ pub struct Game {
     unavailable: Board,
 }
 
+/// Represents the reason a move cannot be played.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// The game is over.
+    GameOver,
+
+    /// The square is not on the board.
+    InvalidSquare(u8),
+
+    /// The square is occupied.
+    Occupied(u8),
+
+    /// The square is unoccupied but unavailable due to the previous player's
+    /// move.
+    Unavailable(u8),
+
+    /// The current player's hand does not contain the tile.
+    NotInHand(Tile),
+
+    /// The current player cannot pass as a move is available.
+    PassNotAllowed,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::GameOver => f.write_str("the game is over"),
+            MoveError::InvalidSquare(square) => write!(f, "square {square} is not on the board"),
+            MoveError::Occupied(square) => write!(f, "square {square} is occupied"),
+            MoveError::Unavailable(square) => {
+                write!(f, "square {square} is unavailable due to the previous move")
+            }
+            MoveError::NotInHand(tile) => write!(f, "no {tile} is left in the hand"),
+            MoveError::PassNotAllowed => {
+                f.write_str("a move is available, so passing is not allowed")
+            }
+        }
+    }
+}
+
+impl Error for MoveError {}
+
 impl Default for Game {
     /// Returns the initial game state, with no squares being occupied and each
     /// player having a complete hand.
@@ -49,10 +100,178 @@ impl Default for Game {
 }
 
 impl Game {
+    /// Returns the initial game state, with no squares being occupied, each
+    /// player having a complete hand, and every square allowed by the rules
+    /// being available for the first move.
+    ///
+    /// Unlike `default`, this does not exclude first moves that are rotations
+    /// or reflections of other first moves, so is suitable for playing games.
+    pub fn initial() -> Self {
+        Game {
+            unavailable: Board::from(START_UNAVAILABLE),
+            ..Game::default()
+        }
+    }
+
+    /// Returns an instance with the specified state.
+    ///
+    /// Occupied squares are always unavailable, so are added to the board of
+    /// unavailable squares if they are not already included.
+    pub fn new(
+        board: Board,
+        hand: Hand,
+        opponent_board: Board,
+        opponent_hand: Hand,
+        unavailable: Board,
+    ) -> Self {
+        Game {
+            board,
+            hand,
+            opponent_board,
+            opponent_hand,
+            unavailable: Board::merge_3(board, opponent_board, unavailable),
+        }
+    }
+
+    /// Returns an instance with the specified hands for the current player and
+    /// the opposing player, such as to start a game with handicap hands.
+    pub fn with_hands(&self, hand: Hand, opponent_hand: Hand) -> Self {
+        Game {
+            hand,
+            opponent_hand,
+            ..*self
+        }
+    }
+
+    /// Returns the board of squares occupied by the current player.
+    pub fn get_board(&self) -> Board {
+        self.board
+    }
+
+    /// Returns the current player's hand.
+    pub fn get_hand(&self) -> Hand {
+        self.hand
+    }
+
+    /// Returns the board of squares occupied by the opposing player.
+    pub fn get_opponent_board(&self) -> Board {
+        self.opponent_board
+    }
+
+    /// Returns the opposing player's hand.
+    pub fn get_opponent_hand(&self) -> Hand {
+        self.opponent_hand
+    }
+
+    /// Returns the board of squares unavailable to the current player.
+    pub fn get_unavailable(&self) -> Board {
+        self.unavailable
+    }
+
     /// Returns whether the game is over (because the current player's hand is
-    /// empty).
+    /// empty, or every square is occupied).
+    ///
+    /// With the standard hands the board is only full once both hands are
+    /// empty, but hands set with `with_hands` may contain more tiles than there
+    /// are squares.
     pub fn is_over(&self) -> bool {
-        self.hand.is_empty()
+        self.hand.is_empty() || Board::merge(self.board, self.opponent_board).is_full()
+    }
+
+    /// Returns the number of tiles remaining in both players' hands.
+    pub fn tiles_remaining(&self) -> u8 {
+        self.hand.len() + self.opponent_hand.len()
+    }
+
+    /// Returns a key uniquely identifying the game state.
+    ///
+    /// From the least significant bit, the key contains the board of squares
+    /// occupied by the current player, the board of squares occupied by the
+    /// opposing player, the board of unavailable squares, the current player's
+    /// hand, and the opposing player's hand.
+    pub fn key(&self) -> u128 {
+        u128::from(u16::from(self.board))
+            | u128::from(u16::from(self.opponent_board)) << 16
+            | u128::from(u16::from(self.unavailable)) << 32
+            | u128::from(self.hand.to_bits()) << 48
+            | u128::from(self.opponent_hand.to_bits()) << 80
+    }
+
+    /// Returns the game state identified by the specified key, as returned by
+    /// `key`.
+    pub fn from_key(key: u128) -> Self {
+        Game {
+            board: Board::from(key as u16),
+            opponent_board: Board::from((key >> 16) as u16),
+            unavailable: Board::from((key >> 32) as u16),
+            hand: Hand::from_bits((key >> 48) as u32),
+            opponent_hand: Hand::from_bits((key >> 80) as u32),
+        }
+    }
+
+    /// Returns the game state identified by the specified key, as returned by
+    /// `key`, or `None` if the key could not have been returned by `key`: if
+    /// either hand holds more than `Hand::MAX_TILES` tiles, if the players'
+    /// boards overlap, or if an occupied square is not unavailable.
+    ///
+    /// This is for keys that are not trusted, such as those read from a file.
+    pub fn try_from_key(key: u128) -> Option<Self> {
+        let (board, opponent_board, unavailable) =
+            (key as u16, (key >> 16) as u16, (key >> 32) as u16);
+
+        let valid = key >> 112 == 0
+            && board & opponent_board == 0
+            && (board | opponent_board) & !unavailable == 0;
+
+        valid.then_some(Game {
+            board: Board::from(board),
+            opponent_board: Board::from(opponent_board),
+            unavailable: Board::from(unavailable),
+            hand: Hand::try_new(((key >> 48) as u32).to_le_bytes())?,
+            opponent_hand: Hand::try_new(((key >> 80) as u32).to_le_bytes())?,
+        })
+    }
+
+    /// Returns an instance transformed by one of the eight symmetries of the
+    /// board, as described for `Board::transform`.
+    ///
+    /// The transformed instance has the same victory status, as the squares
+    /// made unavailable by each tile are transformed in the same way as the
+    /// square in which it is played.
+    pub fn transform(&self, symmetry: u8) -> Self {
+        Game {
+            board: self.board.transform(symmetry),
+            hand: self.hand,
+            opponent_board: self.opponent_board.transform(symmetry),
+            opponent_hand: self.opponent_hand,
+            unavailable: self.unavailable.transform(symmetry),
+        }
+    }
+
+    /// Returns the canonical instance among the eight symmetric equivalents
+    /// of the game state, which is the one with the lowest key.
+    ///
+    /// Symmetric game states have the same canonical instance, so can share
+    /// results.
+    pub fn canonical(&self) -> Self {
+        self.transform(self.canonical_symmetry())
+    }
+
+    /// Returns the symmetry that transforms the game state into its canonical
+    /// instance, so that moves in the canonical instance can be transformed
+    /// back with `Board::inverse_symmetry`.
+    pub fn canonical_symmetry(&self) -> u8 {
+        (1..8)
+            .fold((0, self.key()), |canonical, symmetry| {
+                let key = self.transform(symmetry).key();
+
+                if key < canonical.1 {
+                    (symmetry, key)
+                } else {
+                    canonical
+                }
+            })
+            .0
     }
 
     /// Returns the victory status.
@@ -69,6 +288,22 @@ impl Game {
         }
     }
 
+    /// Returns the margin by which the current player's score exceeds the
+    /// opposing player's score, which is negative if the current player is
+    /// behind.
+    pub fn get_margin(&self) -> i8 {
+        self.board.get_score() as i8 - self.opponent_board.get_score() as i8
+    }
+
+    /// Returns an iterator over the moves the current player can legally make,
+    /// which is a single pass if the player must pass, and empty if the game
+    /// is over.
+    ///
+    /// The solver does not use this, as it tries moves in a different order.
+    pub fn legal_moves(&self) -> LegalMoves {
+        LegalMoves::new(self)
+    }
+
     /// Returns whether the player must pass (because all squares are
     /// unavailable).
     pub fn player_must_pass(&self) -> bool {
@@ -111,16 +346,110 @@ impl Game {
             ),
         }
     }
+
+    /// Returns an instance for the opponent after the current player has
+    /// passed, or an error if passing is not allowed because the game is over
+    /// or a move is available.
+    pub fn try_with_pass(&self) -> Result<Self, MoveError> {
+        if self.is_over() {
+            Err(MoveError::GameOver)
+        } else if !self.player_must_pass() {
+            Err(MoveError::PassNotAllowed)
+        } else {
+            Ok(self.with_pass())
+        }
+    }
+
+    /// Returns an instance for the opponent after the specified move, or an
+    /// error if the move is not allowed.
+    ///
+    /// Unlike `with_move`, which the solver uses for speed, this checks that the
+    /// game is not over, that the square is on the board and available, and
+    /// that the current player's hand contains the tile.
+    pub fn try_with_move(&self, tile: Tile, square: u8) -> Result<Self, MoveError> {
+        if self.is_over() {
+            Err(MoveError::GameOver)
+        } else if square >= 16 {
+            Err(MoveError::InvalidSquare(square))
+        } else if !Board::merge(self.board, self.opponent_board).is_available(square) {
+            Err(MoveError::Occupied(square))
+        } else if !self.is_available(square) {
+            Err(MoveError::Unavailable(square))
+        } else if !self.has(tile) {
+            Err(MoveError::NotInHand(tile))
+        } else {
+            Ok(self.with_move(tile, square))
+        }
+    }
+
+    /// Returns an instance for the opponent after the specified move, or an
+    /// error if the move is not allowed, as described for `try_with_pass` and
+    /// `try_with_move`.
+    pub fn try_after(&self, player_move: Move) -> Result<Self, MoveError> {
+        match player_move {
+            Move::Play(tile, square) => self.try_with_move(tile, square),
+            Move::Pass => self.try_with_pass(),
+        }
+    }
+
+    /// Returns an instance for the opponent after the specified move, which
+    /// may be either playing a tile or passing.
+    pub fn after(&self, player_move: Move) -> Self {
+        match player_move {
+            Move::Play(tile, square) => self.with_move(tile, square),
+            Move::Pass => self.with_pass(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Game;
+    use super::{Game, MoveError};
     use crate::board::Board;
     use crate::hand::Hand;
+    use crate::moves::Move;
     use crate::status::Status::*;
     use crate::tile::Tile::*;
 
+    #[test]
+    fn initial() {
+        let game = Game::initial();
+        assert!(game.is_available(0));
+        assert!(game.is_available(15));
+        assert!(!game.is_available(5));
+    }
+
+    #[test]
+    fn new() {
+        let game = Game::new(
+            Board::from(0b_0000_0000_0000_0001),
+            Hand::default().without(Pusher),
+            Board::from(0b_0000_0000_0000_0010),
+            Hand::default().without(Puller),
+            Board::from(0b_1111_0000_0000_0000),
+        );
+
+        assert_eq!(Board::from(0b_0000_0000_0000_0001), game.get_board());
+        assert_eq!(Hand::default().without(Pusher), game.get_hand());
+        assert_eq!(
+            Board::from(0b_0000_0000_0000_0010),
+            game.get_opponent_board()
+        );
+        assert_eq!(Hand::default().without(Puller), game.get_opponent_hand());
+        assert_eq!(Board::from(0b_1111_0000_0000_0011), game.get_unavailable());
+    }
+
+    #[test]
+    fn with_hands() {
+        let hand = Hand::new([0, 3, 3, 2]);
+        let game = Game::initial().with_hands(hand, Hand::default());
+
+        assert_eq!(hand, game.get_hand());
+        assert_eq!(Hand::default(), game.get_opponent_hand());
+        assert_eq!(Game::initial().get_unavailable(), game.get_unavailable());
+        assert!(!game.has(Puller));
+    }
+
     #[test]
     fn is_over() {
         assert!(!Game::default().is_over());
@@ -139,6 +468,108 @@ mod tests {
             ..Game::default()
         }
         .is_over());
+
+        // The game is over once every square is occupied, even if tiles
+        // remain in the hands.
+        let game: Game = "XxXx/xXxX/XxXx/xXx. 2000 1000 0000".parse().unwrap();
+        assert!(!game.is_over());
+        assert!(game.with_move(Puller, 15).is_over());
+
+        let mut games = 0;
+        assert_eq!(Draw, crate::solver::solve(game, &mut games));
+        assert_eq!(1, games);
+    }
+
+    #[test]
+    fn tiles_remaining() {
+        assert_eq!(16, Game::default().tiles_remaining());
+        assert_eq!(15, Game::default().with_move(Pusher, 0).tiles_remaining());
+    }
+
+    #[test]
+    fn key() {
+        assert_eq!(0x0202_0202_0202_0202_fffc_0000_0000, Game::default().key());
+
+        assert_eq!(
+            0x0202_0202_0202_0201_f0ff_0001_000e,
+            Game {
+                board: Board::from(0b_0000_0000_0000_1110),
+                hand: Hand::default().without(Puller),
+                opponent_board: Board::from(0b_0000_0000_0000_0001),
+                unavailable: Board::from(0b_1111_0000_1111_1111),
+                ..Game::default()
+            }
+            .key()
+        );
+    }
+
+    #[test]
+    fn from_key() {
+        let game = Game::default().with_move(Pusher, 1).with_move(Puller, 8);
+        assert_eq!(game, Game::from_key(game.key()));
+    }
+
+    #[test]
+    fn try_from_key() {
+        let game = Game::default().with_move(Pusher, 1).with_move(Puller, 8);
+        assert_eq!(Some(game), Game::try_from_key(game.key()));
+
+        // Overlapping boards.
+        assert_eq!(None, Game::try_from_key(game.key() | 1 << (16 + 1)));
+
+        // An occupied square that is available.
+        assert_eq!(None, Game::try_from_key(game.key() & !(1 << (32 + 8))));
+
+        // A hand with too many tiles.
+        assert_eq!(None, Game::try_from_key(game.key() | 0xff << 48));
+
+        // Bits beyond the hands.
+        assert_eq!(None, Game::try_from_key(game.key() | 1 << 120));
+    }
+
+    #[test]
+    fn transform() {
+        let game = Game::default().with_move(Pusher, 1).with_move(Puller, 8);
+        let transformed = game.transform(0b_101);
+
+        assert_eq!(transformed.board, Board::default().with(8));
+        assert_eq!(transformed.hand, game.hand);
+        assert_eq!(transformed.opponent_board, Board::default().with(14));
+        assert_eq!(transformed.opponent_hand, game.opponent_hand);
+        assert_eq!(
+            transformed,
+            Game::default().with_move(Pusher, 8).with_move(Puller, 14)
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let game = Game::default()
+            .with_move(Pusher, 0)
+            .with_move(Straight, 10)
+            .with_move(Diagonal, 3);
+
+        for symmetry in 0..8 {
+            assert_eq!(game.canonical(), game.transform(symmetry).canonical());
+        }
+
+        assert_eq!(
+            game.canonical(),
+            Game::default()
+                .with_move(Pusher, 0)
+                .with_move(Straight, 10)
+                .with_move(Diagonal, 12)
+                .canonical()
+        );
+
+        assert_ne!(
+            game.canonical(),
+            Game::default()
+                .with_move(Pusher, 0)
+                .with_move(Straight, 10)
+                .with_move(Diagonal, 15)
+                .canonical()
+        );
     }
 
     #[test]
@@ -164,6 +595,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_margin() {
+        assert_eq!(0, Game::default().get_margin());
+
+        let game = Game {
+            board: Board::from(0b_0000_0000_0000_0111),
+            opponent_board: Board::from(0b_0100_1110_0111_0010),
+            ..Game::default()
+        };
+        assert_eq!(-5, game.get_margin());
+        assert_eq!(5, game.with_pass().get_margin());
+    }
+
     #[test]
     fn player_must_pass() {
         assert!(!Game::default().player_must_pass());
@@ -237,4 +681,82 @@ mod tests {
         assert_eq!(after_move.opponent_hand, Hand::default().without(Diagonal));
         assert_eq!(after_move.unavailable, Board::from(0b_1111_1010_1111_1111));
     }
+
+    #[test]
+    fn after() {
+        let game = Game::default().with_move(Pusher, 0);
+        assert_eq!(game.with_pass(), game.after(Move::Pass));
+        assert_eq!(
+            game.with_move(Diagonal, 15),
+            game.after(Move::Play(Diagonal, 15))
+        );
+    }
+
+    #[test]
+    fn try_with_pass() {
+        // A Puller in the corner only allows moves in squares 1, 4, and 5,
+        // which are occupied, so the next player must pass.
+        let game = Game::initial()
+            .with_move(Pusher, 1)
+            .with_move(Pusher, 4)
+            .with_move(Diagonal, 5)
+            .with_move(Puller, 0);
+
+        assert_eq!(Ok(game.with_pass()), game.try_with_pass());
+        assert_eq!(
+            Err(MoveError::PassNotAllowed),
+            game.with_pass().try_with_pass()
+        );
+        assert_eq!(
+            Err(MoveError::GameOver),
+            Game {
+                hand: Hand::new([0, 0, 0, 0]),
+                ..game
+            }
+            .try_with_pass()
+        );
+    }
+
+    #[test]
+    fn try_with_move() {
+        let game = Game::initial().with_move(Straight, 0);
+
+        assert_eq!(Ok(game.with_move(Pusher, 3)), game.try_with_move(Pusher, 3));
+        assert_eq!(
+            Err(MoveError::InvalidSquare(16)),
+            game.try_with_move(Pusher, 16)
+        );
+        assert_eq!(Err(MoveError::Occupied(0)), game.try_with_move(Pusher, 0));
+        assert_eq!(
+            Err(MoveError::Unavailable(5)),
+            game.try_with_move(Pusher, 5)
+        );
+        assert_eq!(
+            Err(MoveError::NotInHand(Pusher)),
+            Game {
+                hand: Hand::new([2, 0, 2, 2]),
+                ..game
+            }
+            .try_with_move(Pusher, 3)
+        );
+        assert_eq!(
+            Err(MoveError::GameOver),
+            Game {
+                hand: Hand::new([0, 0, 0, 0]),
+                ..game
+            }
+            .try_with_move(Pusher, 3)
+        );
+    }
+
+    #[test]
+    fn try_after() {
+        let game = Game::initial().with_move(Straight, 0);
+
+        assert_eq!(
+            Ok(game.with_move(Diagonal, 12)),
+            game.try_after(Move::Play(Diagonal, 12))
+        );
+        assert_eq!(Err(MoveError::PassNotAllowed), game.try_after(Move::Pass));
+    }
 }
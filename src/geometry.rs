@@ -0,0 +1,454 @@
+//! Defines a struct describing the shape of the board and its scoring lines.
+
+use crate::board::Board;
+use crate::game::Game;
+use crate::status::Status;
+use crate::tile::Tile;
+
+/// Describes the shape of a board within the 4×4 grid of squares represented
+/// by `Board`, and the lines of squares that score.
+///
+/// The standard board uses every square of the grid, with lines of three
+/// squares. Variant boards can leave squares out of play, score longer or
+/// shorter lines, or arbitrary sets of squares, or place up to 16 squares at
+/// arbitrary rows and columns, such as the squares of an `Arrangement` of the
+/// four quarters of the board.
+///
+/// The bit fields used by `Board`, `Tile`, and `Game` are fixed at compile
+/// time for the standard board, so the equivalent tables are calculated when
+/// the geometry is created, and the methods here should be used instead. Tiles
+/// constrain the next move by the row and column of each square within the
+/// grid, whether or not the squares in between are playable.
+///
+/// The game is over when the player to move has no tiles left, or when every
+/// playable square is occupied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardGeometry {
+    /// The board of playable squares.
+    playable: Board,
+
+    /// The bit fields representing the lines of squares that score.
+    lines: Vec<u16>,
+
+    /// The squares unavailable for the first move, in addition to those that
+    /// are not playable.
+    start_unavailable: Board,
+
+    /// An array whose `i`th element is the score for the board whose `u16`
+    /// value is `i`.
+    scores: Vec<u8>,
+
+    /// The bit fields representing the squares unavailable after a move,
+    /// indexed by tile discriminant and the square in which the tile was
+    /// played, including the squares that are not playable.
+    moves: [[u16; 16]; 4],
+}
+
+impl Default for BoardGeometry {
+    /// Returns the geometry of the standard board.
+    fn default() -> Self {
+        BoardGeometry::new(Board::from(u16::MAX), 3)
+            .with_start_unavailable(Game::initial().get_unavailable())
+    }
+}
+
+impl BoardGeometry {
+    /// Returns the geometry with the specified playable squares, scoring every
+    /// horizontal, vertical, and diagonal line of the specified number of
+    /// consecutive playable squares.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line length is less than 2. A line of one square would be
+    /// counted once in each direction.
+    pub fn new(playable: Board, line_length: u8) -> Self {
+        BoardGeometry::from_coordinates(grid_coordinates(playable), line_length)
+    }
+
+    /// Returns the geometry with the specified playable squares and lines of
+    /// squares that score.
+    ///
+    /// Squares in a line that are not playable can never be occupied, so the
+    /// line can never score.
+    pub fn with_lines(playable: Board, lines: Vec<Board>) -> Self {
+        BoardGeometry::build(
+            grid_coordinates(playable),
+            lines.into_iter().map(u16::from).collect(),
+        )
+    }
+
+    /// Returns the geometry with playable squares at the specified rows and
+    /// columns, which need not fit within the 4×4 grid, scoring every
+    /// horizontal, vertical, and diagonal line of the specified number of
+    /// consecutive playable squares.
+    ///
+    /// Square `i` of the board is the `i`th square specified, and any squares
+    /// beyond those specified are not playable. Tiles constrain the next move
+    /// by the specified rows and columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than 16 squares are specified, or if the line length is
+    /// less than 2.
+    pub fn from_squares(squares: &[(i8, i8)], line_length: u8) -> Self {
+        assert!(squares.len() <= 16, "a board has at most 16 squares");
+
+        let mut coordinates = [None; 16];
+
+        for (square, &coordinate) in squares.iter().enumerate() {
+            coordinates[square] = Some(coordinate);
+        }
+
+        BoardGeometry::from_coordinates(coordinates, line_length)
+    }
+
+    /// Returns the geometry with the specified row and column for each
+    /// playable square, scoring every line of the specified number of
+    /// consecutive playable squares.
+    fn from_coordinates(coordinates: [Option<(i8, i8)>; 16], line_length: u8) -> Self {
+        assert!(line_length >= 2, "lines have at least 2 squares");
+
+        let find = |coordinate| {
+            coordinates
+                .iter()
+                .position(|&other| other == Some(coordinate))
+        };
+
+        let mut lines = Vec::new();
+
+        for (row, column) in coordinates.iter().flatten().copied() {
+            for (row_step, column_step) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                // A line running beyond the range of an `i8` has no square
+                // there, so cannot score.
+                let line = (0..line_length as i8).try_fold(0, |line, index| {
+                    let row = row.checked_add(row_step * index)?;
+                    let column = column.checked_add(column_step * index)?;
+                    find((row, column)).map(|square| line | 1 << square)
+                });
+
+                if let Some(line) = line {
+                    if line != 0 {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        BoardGeometry::build(coordinates, lines)
+    }
+
+    /// Returns the geometry with the specified row and column for each
+    /// playable square and the specified lines of squares that score.
+    fn build(coordinates: [Option<(i8, i8)>; 16], lines: Vec<u16>) -> Self {
+        let playable = coordinates
+            .iter()
+            .enumerate()
+            .filter(|(_, coordinate)| coordinate.is_some())
+            .fold(Board::default(), |playable, (square, _)| {
+                playable.with(square as u8)
+            });
+
+        let scores = (0..=u16::MAX)
+            .map(|board| lines.iter().filter(|&&line| board & line == line).count() as u8)
+            .collect();
+
+        let mut moves = [[u16::MAX; 16]; 4];
+
+        for tile in Tile::ALL {
+            for (square, &coordinate) in coordinates.iter().enumerate() {
+                let Some((row, column)) = coordinate else {
+                    continue;
+                };
+
+                for (other, &other_coordinate) in coordinates.iter().enumerate() {
+                    if let Some((other_row, other_column)) = other_coordinate {
+                        if tile.allows(
+                            other_row as i16 - row as i16,
+                            other_column as i16 - column as i16,
+                        ) {
+                            moves[tile as usize][square] &= !(1 << other);
+                        }
+                    }
+                }
+            }
+        }
+
+        BoardGeometry {
+            playable,
+            lines,
+            start_unavailable: Board::default(),
+            scores,
+            moves,
+        }
+    }
+
+    /// Returns the geometry with the specified squares unavailable for the
+    /// first move, such as the central squares on the standard board.
+    pub fn with_start_unavailable(self, start_unavailable: Board) -> Self {
+        BoardGeometry {
+            start_unavailable,
+            ..self
+        }
+    }
+
+    /// Returns the board of playable squares.
+    pub fn get_playable(&self) -> Board {
+        self.playable
+    }
+
+    /// Returns the lines of squares that score.
+    pub fn get_lines(&self) -> Vec<Board> {
+        self.lines.iter().copied().map(Board::from).collect()
+    }
+
+    /// Returns the initial game state, with no squares being occupied, each
+    /// player having a complete hand, and every playable square allowed for the
+    /// first move being available.
+    pub fn initial_game(&self) -> Game {
+        self.restrict(Game::new(
+            Board::default(),
+            Default::default(),
+            Board::default(),
+            Default::default(),
+            self.start_unavailable,
+        ))
+    }
+
+    /// Returns the specified game state with the squares that are not playable
+    /// marked as unavailable.
+    pub fn restrict(&self, game: Game) -> Game {
+        Game::new(
+            game.get_board(),
+            game.get_hand(),
+            game.get_opponent_board(),
+            game.get_opponent_hand(),
+            Board::from(u16::from(game.get_unavailable()) | self.unplayable()),
+        )
+    }
+
+    /// Returns the score for the specified board.
+    pub fn get_score(&self, board: Board) -> u8 {
+        self.scores[u16::from(board) as usize]
+    }
+
+    /// Returns the board of squares unavailable after playing the specified
+    /// tile in the specified square.
+    pub fn get_unavailable(&self, tile: Tile, square: u8) -> Board {
+        Board::from(self.moves[tile as usize][square as usize])
+    }
+
+    /// Returns whether the game is over, because the current player's hand is
+    /// empty or every playable square is occupied.
+    pub fn is_over(&self, game: &Game) -> bool {
+        game.is_over()
+            || Board::from(
+                u16::from(Board::merge(game.get_board(), game.get_opponent_board()))
+                    | self.unplayable(),
+            )
+            .is_full()
+    }
+
+    /// Returns the victory status of the specified game state.
+    pub fn get_status(&self, game: &Game) -> Status {
+        match self.get_margin(game) {
+            margin if margin > 0 => Status::Win,
+            0 => Status::Draw,
+            _ => Status::Loss,
+        }
+    }
+
+    /// Returns the margin by which the current player's score exceeds the
+    /// opposing player's score, which is negative if the current player is
+    /// behind.
+    pub fn get_margin(&self, game: &Game) -> i8 {
+        self.get_score(game.get_board()) as i8 - self.get_score(game.get_opponent_board()) as i8
+    }
+
+    /// Returns an instance for the opponent after the current player has
+    /// passed, allowing the opponent to play in any unoccupied playable
+    /// square.
+    pub fn with_pass(&self, game: &Game) -> Game {
+        Game::new(
+            game.get_opponent_board(),
+            game.get_opponent_hand(),
+            game.get_board(),
+            game.get_hand(),
+            Board::from(self.unplayable()),
+        )
+    }
+
+    /// Returns an instance for the opponent after the specified move.
+    pub fn with_move(&self, game: &Game, tile: Tile, square: u8) -> Game {
+        Game::new(
+            game.get_opponent_board(),
+            game.get_opponent_hand(),
+            game.get_board().with(square),
+            game.get_hand().without(tile),
+            self.get_unavailable(tile, square),
+        )
+    }
+
+    /// Returns the bit field of squares that are not playable.
+    fn unplayable(&self) -> u16 {
+        !u16::from(self.playable)
+    }
+}
+
+/// Returns the row and column within the 4×4 grid of each playable square.
+fn grid_coordinates(playable: Board) -> [Option<(i8, i8)>; 16] {
+    let mut coordinates = [None; 16];
+
+    for square in 0..16 {
+        if !playable.is_available(square) {
+            coordinates[square as usize] = Some(((square / 4) as i8, (square % 4) as i8));
+        }
+    }
+
+    coordinates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn new() {
+        let geometry = BoardGeometry::default();
+        assert_eq!(24, geometry.get_lines().len());
+
+        // A 3×3 board in the top left corner has three rows, three columns,
+        // and two diagonals.
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0111_0111_0111), 3);
+        assert_eq!(8, geometry.get_lines().len());
+
+        let geometry = BoardGeometry::new(Board::from(u16::MAX), 4);
+        assert_eq!(10, geometry.get_lines().len());
+    }
+
+    #[test]
+    fn from_squares() {
+        let squares: Vec<(i8, i8)> = (0..16).map(|square| (square / 4, square % 4)).collect();
+        assert_eq!(
+            BoardGeometry::new(Board::from(u16::MAX), 3),
+            BoardGeometry::from_squares(&squares, 3)
+        );
+
+        // A 2×8 strip has six lines of three squares in each row, and a
+        // Straight in the first square allows moves along the row and in the
+        // square below.
+        let squares: Vec<(i8, i8)> = (0..16).map(|square| (square / 8, square % 8)).collect();
+        let geometry = BoardGeometry::from_squares(&squares, 3);
+        assert_eq!(12, geometry.get_lines().len());
+        assert_eq!(
+            0b_1111_1110_0000_0001,
+            u16::from(geometry.get_unavailable(Straight, 0))
+        );
+
+        // Squares beyond those specified are not playable.
+        let geometry = BoardGeometry::from_squares(&[(0, 0), (0, 1), (0, 2)], 3);
+        assert_eq!(0b_0000_0000_0000_0111, u16::from(geometry.get_playable()));
+        assert_eq!(1, geometry.get_lines().len());
+    }
+
+    #[test]
+    fn get_score() {
+        let geometry = BoardGeometry::default();
+
+        for board in 0..=u16::MAX {
+            assert_eq!(
+                Board::from(board).get_score(),
+                geometry.get_score(Board::from(board))
+            );
+        }
+
+        let geometry = BoardGeometry::with_lines(
+            Board::from(u16::MAX),
+            vec![Board::from(0b_1001_0000_0000_1001)],
+        );
+        assert_eq!(1, geometry.get_score(Board::from(0b_1001_0000_0000_1001)));
+        assert_eq!(0, geometry.get_score(Board::from(0b_0000_0000_0000_0111)));
+    }
+
+    #[test]
+    fn get_unavailable() {
+        let geometry = BoardGeometry::default();
+
+        for tile in Tile::ALL {
+            for square in 0..16 {
+                assert_eq!(
+                    tile.get_unavailable(square),
+                    geometry.get_unavailable(tile, square)
+                );
+            }
+        }
+
+        // Squares that are not playable are always unavailable.
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0111_0111_0111), 3);
+        assert_eq!(
+            0b_1111_1000_1010_1000,
+            u16::from(geometry.get_unavailable(Puller, 5))
+        );
+    }
+
+    #[test]
+    fn initial_game() {
+        assert_eq!(Game::initial(), BoardGeometry::default().initial_game());
+
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0111_0111_0111), 3);
+        assert_eq!(
+            0b_1111_1000_1000_1000,
+            u16::from(geometry.initial_game().get_unavailable())
+        );
+    }
+
+    #[test]
+    fn is_over() {
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0000_0000_0011), 3);
+        let game = geometry.initial_game();
+        assert!(!geometry.is_over(&game));
+
+        // A Pusher makes the adjacent square unavailable, so the opposing
+        // player must pass, after which every playable square is occupied.
+        let game = geometry.with_move(&game, Pusher, 0);
+        assert!(game.player_must_pass());
+        assert!(!geometry.is_over(&game));
+
+        let game = geometry.with_move(&geometry.with_pass(&game), Puller, 1);
+        assert!(geometry.is_over(&game));
+    }
+
+    #[test]
+    fn with_move_and_with_pass() {
+        let geometry = BoardGeometry::default();
+        let game = Game::initial();
+
+        assert_eq!(
+            game.with_move(Straight, 0),
+            geometry.with_move(&game, Straight, 0)
+        );
+        assert_eq!(
+            game.with_move(Straight, 0).with_pass(),
+            geometry.with_pass(&geometry.with_move(&game, Straight, 0))
+        );
+    }
+
+    #[test]
+    fn get_status_and_get_margin() {
+        let geometry = BoardGeometry::default();
+        let game = Game::new(
+            Board::from(0b_0000_0000_0000_0111),
+            Default::default(),
+            Board::from(0b_0000_0000_0111_0000),
+            Default::default(),
+            Board::default(),
+        );
+
+        assert_eq!(0, geometry.get_margin(&game));
+        assert_eq!(Status::Draw, geometry.get_status(&game));
+
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0000_0000_1111), 3);
+        assert_eq!(1, geometry.get_margin(&game));
+        assert_eq!(Status::Win, geometry.get_status(&game));
+    }
+}
@@ -17,9 +17,44 @@ impl Default for Hand {
 }
 
 impl Hand {
+    /// The maximum number of tiles in a hand, which is the number of squares on
+    /// the standard board, as the game ends once every square is occupied.
+    pub const MAX_TILES: u8 = 16;
+
+    /// Returns a hand containing the specified number of each tile, indexed by
+    /// tile discriminant.
+    ///
+    /// Starting hands need not contain two of each tile, so the players can be
+    /// given different tiles, or different numbers of tiles, to study how the
+    /// mix of tiles affects the result.
+    ///
+    /// Panics if the hand would contain more than `Hand::MAX_TILES` tiles.
+    pub fn new(counts: [u8; 4]) -> Self {
+        Hand::try_new(counts)
+            .unwrap_or_else(|| panic!("a hand holds at most {} tiles", Hand::MAX_TILES))
+    }
+
+    /// Returns a hand containing the specified number of each tile, indexed by
+    /// tile discriminant, or `None` if it would contain more than
+    /// `Hand::MAX_TILES` tiles.
+    pub fn try_new(counts: [u8; 4]) -> Option<Self> {
+        let len: u16 = counts.iter().map(|&count| u16::from(count)).sum();
+        (len <= u16::from(Hand::MAX_TILES)).then_some(Hand(counts))
+    }
+
     /// Returns whether the hand is empty.
     pub fn is_empty(&self) -> bool {
-        (self.0[0] + self.0[1] + self.0[2] + self.0[3]) == 0
+        self.len() == 0
+    }
+
+    /// Returns the number of tiles in the hand.
+    pub fn len(&self) -> u8 {
+        self.0[0] + self.0[1] + self.0[2] + self.0[3]
+    }
+
+    /// Returns the number of the specified tile in the hand.
+    pub fn count(&self, tile: Tile) -> u8 {
+        self.0[tile as usize]
     }
 
     /// Returns whether the hand contains the specified tile.
@@ -27,6 +62,21 @@ impl Hand {
         self.0[tile as usize] > 0
     }
 
+    /// Returns the number of each tile packed into a `u32`, with one byte per
+    /// tile in order of discriminant from the least significant byte.
+    pub fn to_bits(&self) -> u32 {
+        u32::from_le_bytes(self.0)
+    }
+
+    /// Returns the hand represented by the specified bits, as returned by
+    /// `to_bits`.
+    ///
+    /// The bits are not checked, so must represent a hand with at most
+    /// `Hand::MAX_TILES` tiles. Use `try_new` for bits that are not trusted.
+    pub fn from_bits(bits: u32) -> Self {
+        Hand(bits.to_le_bytes())
+    }
+
     /// Returns a new hand with one of the specified tile having been removed.
     pub fn without(&self, tile: Tile) -> Self {
         let mut hand = *self;
@@ -40,6 +90,20 @@ mod tests {
     use super::Hand;
     use crate::tile::Tile::*;
 
+    #[test]
+    fn try_new() {
+        assert_eq!(Some(Hand([1, 2, 3, 4])), Hand::try_new([1, 2, 3, 4]));
+        assert_eq!(Some(Hand([16, 0, 0, 0])), Hand::try_new([16, 0, 0, 0]));
+        assert_eq!(None, Hand::try_new([4, 4, 4, 5]));
+        assert_eq!(None, Hand::try_new([255, 255, 255, 255]));
+    }
+
+    #[test]
+    #[should_panic(expected = "a hand holds at most 16 tiles")]
+    fn new_too_many_tiles() {
+        Hand::new([0, 0, 9, 9]);
+    }
+
     #[test]
     fn is_empty() {
         assert!(Hand([0, 0, 0, 0]).is_empty());
@@ -49,6 +113,21 @@ mod tests {
         assert!(!Hand([0, 0, 0, 1]).is_empty());
     }
 
+    #[test]
+    fn len() {
+        assert_eq!(0, Hand([0, 0, 0, 0]).len());
+        assert_eq!(10, Hand([1, 2, 3, 4]).len());
+    }
+
+    #[test]
+    fn count() {
+        let hand = Hand::new([1, 2, 3, 4]);
+        assert_eq!(1, hand.count(Puller));
+        assert_eq!(2, hand.count(Pusher));
+        assert_eq!(3, hand.count(Straight));
+        assert_eq!(4, hand.count(Diagonal));
+    }
+
     #[test]
     fn has() {
         assert_has(Hand([1, 0, 0, 0]), true, false, false, false);
@@ -57,6 +136,16 @@ mod tests {
         assert_has(Hand([0, 0, 0, 1]), false, false, false, true);
     }
 
+    #[test]
+    fn to_bits() {
+        assert_eq!(0x0403_0201, Hand([1, 2, 3, 4]).to_bits());
+    }
+
+    #[test]
+    fn from_bits() {
+        assert_eq!(Hand([1, 2, 3, 4]), Hand::from_bits(0x0403_0201));
+    }
+
     #[test]
     fn without() {
         let hand = Hand([1, 1, 1, 1]);
@@ -0,0 +1,57 @@
+//! A library for solving Mijnlieff.
+//!
+//! The types representing the game state are re-exported at the crate root,
+//! so a position can be solved with:
+//!
+//! ```no_run
+//! use mijnlieff_solver::{solver, Game};
+//!
+//! let mut games = 0;
+//! let status = solver::solve(Game::default(), &mut games);
+//! ```
+
+pub mod analysis;
+pub mod arrangement;
+pub mod board;
+pub mod book;
+pub mod database;
+#[doc(hidden)]
+pub mod fixtures;
+pub mod format;
+pub mod game;
+pub mod geometry;
+pub mod hand;
+pub mod limits;
+pub mod moves;
+pub mod notation;
+pub mod parallel;
+pub mod perft;
+pub mod position;
+pub mod progress;
+pub mod search;
+pub mod solver;
+pub mod status;
+pub mod table;
+pub mod tablebase;
+pub mod tile;
+pub mod wide;
+
+pub use analysis::MoveAnalysis;
+pub use arrangement::Arrangement;
+pub use board::Board;
+pub use book::OpeningBook;
+pub use database::SolutionDatabase;
+pub use format::FileError;
+pub use game::{Game, MoveError};
+pub use geometry::BoardGeometry;
+pub use hand::Hand;
+pub use limits::{Limits, SolveOutcome};
+pub use moves::{LegalMoves, Move};
+pub use position::Position;
+pub use progress::Progress;
+pub use search::SearchResult;
+pub use status::Status;
+pub use table::TranspositionTable;
+pub use tablebase::Tablebase;
+pub use tile::Tile;
+pub use wide::{WideBoard, WideGame, WideGeometry};
@@ -0,0 +1,203 @@
+//! Defines structs for stopping a solve before it finishes.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::moves::Move;
+use crate::solver::SolveHook;
+use crate::status::Status::{self, *};
+
+/// The number of games analysed between checks of the time and the
+/// cancellation flags (65,536), so that none of them is read for every game.
+pub(crate) const CHECK_GAMES: u64 = 1 << 16;
+
+/// Represents the limits after which `solver::solve_with_limits` or
+/// `parallel::solve_parallel_with_limits` stops.
+///
+/// The default has no limits, so the solve only stops once it has finished.
+/// The deadline and the cancellation flag are checked every 65,536 games, so
+/// the solve may run up to that many games beyond them, but the game budget is
+/// exact.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits<'a> {
+    /// The time after which the solve stops, if any.
+    deadline: Option<Instant>,
+
+    /// The maximum number of games analysed, if any.
+    max_games: Option<u64>,
+
+    /// A flag that stops the solve once set, if any.
+    cancelled: Option<&'a AtomicBool>,
+}
+
+impl<'a> Limits<'a> {
+    /// Returns an instance that also stops at the specified time.
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Limits {
+            deadline: Some(deadline),
+            ..*self
+        }
+    }
+
+    /// Returns an instance that also stops once the specified time has passed
+    /// from now.
+    pub fn with_time_limit(&self, time_limit: Duration) -> Self {
+        self.with_deadline(Instant::now() + time_limit)
+    }
+
+    /// Returns an instance that also stops once the specified number of games
+    /// have been analysed.
+    pub fn with_max_games(&self, max_games: u64) -> Self {
+        Limits {
+            max_games: Some(max_games),
+            ..*self
+        }
+    }
+
+    /// Returns an instance that also stops once the specified flag is set,
+    /// such as by another thread.
+    pub fn with_cancel_flag(&self, cancelled: &'a AtomicBool) -> Self {
+        Limits {
+            cancelled: Some(cancelled),
+            ..*self
+        }
+    }
+
+    /// Returns the number of games analysed at which the limits should next be
+    /// checked, given the specified number analysed, so that the game budget
+    /// is not exceeded.
+    pub(crate) fn next_check(&self, games: u64) -> u64 {
+        (games + CHECK_GAMES).min(self.max_games.unwrap_or(u64::MAX))
+    }
+
+    /// Returns whether any limit has been reached after the specified number
+    /// of games have been analysed, reading the clock if there is a deadline.
+    pub(crate) fn is_reached(&self, games: u64) -> bool {
+        self.max_games.is_some_and(|max_games| games >= max_games)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            || self
+                .cancelled
+                .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    }
+}
+
+/// Represents the result of `solver::solve_with_limits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// The solve finished, proving the victory status of the position.
+    Solved(Status),
+
+    /// The solve stopped before the status was proven. The field contains the
+    /// moves from the position whose status was proven, in order of
+    /// preference, each with the status of the player making the move.
+    Stopped(Vec<(Move, Status)>),
+}
+
+impl SolveOutcome {
+    /// Returns the proven victory status, or `None` if the solve stopped.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            SolveOutcome::Solved(status) => Some(*status),
+            SolveOutcome::Stopped(_) => None,
+        }
+    }
+
+    /// Returns the best status proven to be achievable, and the first move
+    /// achieving it, or `None` if the solve stopped before any move was solved.
+    ///
+    /// If the solve stopped, the position's status is at least as good as this.
+    pub fn best_proven(&self) -> Option<(Move, Status)> {
+        match self {
+            SolveOutcome::Solved(_) => None,
+            SolveOutcome::Stopped(moves) => {
+                moves
+                    .iter()
+                    .copied()
+                    .reduce(|best, next| match (best.1, next.1) {
+                        (Loss, Draw | Win) | (Draw, Win) => next,
+                        _ => best,
+                    })
+            }
+        }
+    }
+}
+
+/// Checks whether a solve has reached its limits, as a hook for
+/// `solver::solve_hooked`.
+pub(crate) struct Limiter<'a> {
+    /// The limits.
+    limits: Limits<'a>,
+
+    /// The count of games at which the limits are next checked.
+    next_check: u64,
+
+    /// The count of games when the solve started.
+    start_games: u64,
+}
+
+impl<'a> Limiter<'a> {
+    /// Returns a limiter for a solve starting with the specified count of
+    /// games.
+    pub(crate) fn new(limits: Limits<'a>, games: u64) -> Self {
+        Limiter {
+            limits,
+            next_check: games + limits.next_check(0),
+            start_games: games,
+        }
+    }
+
+    /// Checks every limit, returning whether any has been reached.
+    #[cold]
+    fn check(&mut self, games: u64) -> bool {
+        let games = games - self.start_games;
+        self.next_check = self.start_games + self.limits.next_check(games);
+        self.limits.is_reached(games)
+    }
+}
+
+impl SolveHook for Limiter<'_> {
+    #[inline]
+    fn should_stop(&mut self, games: u64) -> bool {
+        games >= self.next_check && self.check(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn limiter() {
+        let mut limiter = Limiter::new(Limits::default().with_max_games(10), 5);
+        assert!(!limiter.should_stop(14));
+        assert!(limiter.should_stop(15));
+
+        let cancelled = AtomicBool::new(false);
+        let mut limiter = Limiter::new(Limits::default().with_cancel_flag(&cancelled), 0);
+        assert!(!limiter.should_stop(CHECK_GAMES));
+        cancelled.store(true, Ordering::Relaxed);
+        assert!(!limiter.should_stop(CHECK_GAMES + 1));
+        assert!(limiter.should_stop(2 * CHECK_GAMES));
+
+        let mut limiter = Limiter::new(Limits::default().with_time_limit(Duration::ZERO), 0);
+        assert!(limiter.should_stop(CHECK_GAMES));
+    }
+
+    #[test]
+    fn best_proven() {
+        assert_eq!(None, SolveOutcome::Solved(Win).best_proven());
+        assert_eq!(None, SolveOutcome::Stopped(Vec::new()).best_proven());
+        assert_eq!(
+            Some((Move::Play(Straight, 3), Draw)),
+            SolveOutcome::Stopped(vec![
+                (Move::Play(Pusher, 5), Loss),
+                (Move::Play(Straight, 3), Draw),
+                (Move::Play(Diagonal, 0), Draw),
+            ])
+            .best_proven()
+        );
+    }
+}
@@ -1,36 +1,14 @@
 //! Solves Mijnlieff.
+//!
+//! Run without arguments to solve the game from the initial position, or run
+//! with `--help` to list the other commands and options.
 
-use std::time::Instant;
+use std::env;
 
-use game::Game;
-use status::Status::*;
+mod cli;
 
-mod board;
-mod game;
-mod hand;
-mod solver;
-mod status;
-mod tile;
-
-/// Solves Mijnlieff and outputs the number of games analysed, the time taken,
-/// and the result.
+/// Runs the command specified by the command line arguments.
 fn main() {
-    let now = Instant::now();
-
-    let mut games = 0;
-    let status = solver::solve(Game::default(), &mut games);
-
-    println!(
-        "Analysed {games} games in {} seconds",
-        now.elapsed().as_secs()
-    );
-
-    println!(
-        "Mijnlieff is a {} with perfect play",
-        match status {
-            Win => "win for the first player",
-            Draw => "draw",
-            Loss => "win for the second player",
-        }
-    );
+    let args: Vec<String> = env::args().skip(1).collect();
+    cli::run(&args);
 }
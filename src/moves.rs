@@ -0,0 +1,235 @@
+//! Defines an enum representing a move, and an iterator over legal moves.
+
+use crate::board::Board;
+use crate::game::Game;
+use crate::tile::Tile;
+
+/// Represents a move: either playing a tile in a square, or passing.
+///
+/// The `Display` and `FromStr` traits are implemented in the `notation` module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Move {
+    Play(Tile, u8),
+    Pass,
+}
+
+impl Move {
+    /// Returns the move transformed by one of the eight symmetries of the
+    /// board, as described for `Board::transform`.
+    pub fn transform(&self, symmetry: u8) -> Move {
+        match *self {
+            Move::Play(tile, square) => Move::Play(
+                tile,
+                u16::from(Board::default().with(square).transform(symmetry)).trailing_zeros() as u8,
+            ),
+            Move::Pass => Move::Pass,
+        }
+    }
+}
+
+/// An iterator over the legal moves in a game position, returned by
+/// `Game::legal_moves`.
+///
+/// Moves are returned in order of square and then tile discriminant, with each
+/// tile type returned once per square however many of it are in the hand. If
+/// the player must pass, the only move returned is `Move::Pass`, and if the
+/// game is over no moves are returned.
+///
+/// The available squares and the tiles in the hand are stored as bit fields,
+/// so only squares that are available are visited.
+#[derive(Debug, Clone)]
+pub struct LegalMoves {
+    /// The bit field of available squares not yet fully visited.
+    squares: u16,
+
+    /// The bit field of tile discriminants in the hand.
+    tiles: u8,
+
+    /// The bit field of tile discriminants not yet returned for the current
+    /// square.
+    remaining_tiles: u8,
+
+    /// Whether a pass is still to be returned.
+    pass: bool,
+}
+
+impl LegalMoves {
+    /// Returns an iterator over the legal moves in the specified game
+    /// position.
+    pub(crate) fn new(game: &Game) -> Self {
+        let mut moves = LegalMoves {
+            squares: 0,
+            tiles: 0,
+            remaining_tiles: 0,
+            pass: false,
+        };
+
+        if game.is_over() {
+            return moves;
+        }
+
+        if game.player_must_pass() {
+            moves.pass = true;
+            return moves;
+        }
+
+        moves.squares = !u16::from(game.get_unavailable());
+        moves.tiles = Tile::ALL
+            .iter()
+            .filter(|&&tile| game.has(tile))
+            .fold(0, |tiles, &tile| tiles | 1 << tile as u8);
+        moves.remaining_tiles = moves.tiles;
+        moves
+    }
+}
+
+impl Iterator for LegalMoves {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.pass {
+            self.pass = false;
+            return Some(Move::Pass);
+        }
+
+        if self.squares == 0 || self.remaining_tiles == 0 {
+            return None;
+        }
+
+        let square = self.squares.trailing_zeros() as u8;
+        let tile = Tile::ALL[self.remaining_tiles.trailing_zeros() as usize];
+
+        // Clear the lowest set bit, moving to the next square once every tile
+        // has been returned for this square.
+        self.remaining_tiles &= self.remaining_tiles - 1;
+
+        if self.remaining_tiles == 0 {
+            self.squares &= self.squares - 1;
+            self.remaining_tiles = self.tiles;
+        }
+
+        Some(Move::Play(tile, square))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = if self.pass {
+            1
+        } else if self.squares == 0 || self.remaining_tiles == 0 {
+            0
+        } else {
+            self.remaining_tiles.count_ones() as usize
+                + (self.squares.count_ones() as usize - 1) * self.tiles.count_ones() as usize
+        };
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for LegalMoves {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn transform() {
+        let game = Game::default().with_move(Pusher, 1);
+
+        for symmetry in 0..8 {
+            assert_eq!(
+                game.transform(symmetry),
+                Game::default()
+                    .transform(symmetry)
+                    .after(Move::Play(Pusher, 1).transform(symmetry))
+            );
+        }
+
+        assert_eq!(Move::Pass, Move::Pass.transform(5));
+    }
+
+    #[test]
+    fn legal_moves() {
+        let moves: Vec<Move> = Game::initial().legal_moves().collect();
+        assert_eq!(48, moves.len());
+        assert_eq!(Move::Play(Puller, 0), moves[0]);
+        assert_eq!(Move::Play(Pusher, 0), moves[1]);
+        assert_eq!(Move::Play(Diagonal, 15), moves[47]);
+
+        // A Straight in the corner only allows moves in the same row or column.
+        let game = Game::initial().with_move(Straight, 0);
+        assert_eq!(
+            vec![1, 2, 3, 4, 8, 12],
+            game.legal_moves()
+                .filter_map(|player_move| match player_move {
+                    Move::Play(Puller, square) => Some(square),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn legal_moves_deduplicated() {
+        let game = Game::new(
+            Board::default(),
+            Hand::new([0, 2, 0, 0]),
+            Board::default(),
+            Hand::default(),
+            Game::initial().get_unavailable(),
+        );
+
+        assert!(game
+            .legal_moves()
+            .all(|player_move| matches!(player_move, Move::Play(Pusher, _))));
+        assert_eq!(12, game.legal_moves().count());
+    }
+
+    #[test]
+    fn legal_moves_pass_and_game_over() {
+        // A Puller in the corner only allows moves in squares 1, 4, and 5,
+        // which are occupied, so the next player must pass.
+        let game = Game::initial()
+            .with_move(Pusher, 1)
+            .with_move(Pusher, 4)
+            .with_move(Diagonal, 5)
+            .with_move(Puller, 0);
+        assert_eq!(vec![Move::Pass], game.legal_moves().collect::<Vec<_>>());
+
+        let game = Game::new(
+            Board::default(),
+            Hand::new([0, 0, 0, 0]),
+            Board::default(),
+            Hand::default(),
+            Board::default(),
+        );
+        assert_eq!(0, game.legal_moves().count());
+    }
+
+    #[test]
+    fn legal_moves_match_try_after() {
+        let mut game = Game::initial();
+
+        for player_move in [
+            Move::Play(Straight, 0),
+            Move::Play(Straight, 3),
+            Move::Play(Pusher, 15),
+            Move::Play(Diagonal, 5),
+        ] {
+            let mut expected: Vec<Move> = (0..16)
+                .flat_map(|square| Tile::ALL.map(|tile| Move::Play(tile, square)))
+                .chain([Move::Pass])
+                .filter(|&player_move| game.try_after(player_move).is_ok())
+                .collect();
+            expected.sort_by_key(|player_move| format!("{player_move:?}"));
+
+            let mut moves: Vec<Move> = game.legal_moves().collect();
+            assert_eq!(moves.len(), game.legal_moves().len());
+            moves.sort_by_key(|player_move| format!("{player_move:?}"));
+
+            assert_eq!(expected, moves);
+            game = game.after(player_move);
+        }
+    }
+}
@@ -0,0 +1,821 @@
+//! Defines a textual notation for moves and game records.
+//!
+//! A move is written as a tile letter followed by a square, such as `P b2`, or
+//! as `pass`. The tile letters are:
+//!
+//! - `L` for a Puller
+//! - `P` for a Pusher
+//! - `S` for a Straight
+//! - `D` for a Diagonal
+//!
+//! Squares are written as a column from `a` to `d` followed by a row from `1`
+//! to `4`, with `a1` being square 0 in the top left corner of the board:
+//!
+//! ```text
+//! a1 b1 c1 d1
+//! a2 b2 c2 d2
+//! a3 b3 c3 d3
+//! a4 b4 c4 d4
+//! ```
+//!
+//! A game record is a list of moves from `Game::initial()` separated by commas
+//! or new lines, such as `S a1, P b2, pass`. When parsing, letters may be in
+//! either case, and the space between the tile letter and the square may be
+//! omitted.
+//!
+//! A position is written as five space-separated fields, such as
+//! `S..s/..../..../...P 2112 2212 cc09 2` after the players have played a
+//! Straight in opposite corners and the first player has played a Pusher:
+//!
+//! 1. The board, as four rows separated by slashes, with each square written
+//!    as `.` if unoccupied, or as the letter of the tile occupying it:
+//!    uppercase for the first player and lowercase for the second player. An
+//!    occupied square whose tile is not known is written as `X` (or `x`).
+//! 2. The hand of the first player, as the number of Pullers, Pushers,
+//!    Straights, and Diagonals.
+//! 3. The hand of the second player, in the same format.
+//! 4. The squares unavailable to the player to move as a hexadecimal bit field,
+//!    as described for `Board`.
+//! 5. The player to move, as `1` for the first player or `2` for the second
+//!    player. This field may be omitted when the first player is to move.
+//!
+//! As `Game` only stores the state necessary to solve the game, it records
+//! neither the tile occupying each square nor which player moved first, so a
+//! `Game` is written as a position with the player to move as the first player,
+//! each occupied square as `X` or `x`, and the fifth field omitted, such as
+//! `x..X/..../..../...x 2212 2112 cc09` for the position above. A `Position`
+//! records both, so is written with every field. Either can be parsed from
+//! either form.
+//!
+//! A board geometry is written as two space-separated fields, such as
+//! `0777 3` for a 3×3 board in the top left corner of the grid:
+//!
+//! 1. The playable squares as a hexadecimal bit field, as described for
+//!    `Board`.
+//! 2. The length of the lines of playable squares that score.
+//!
+//! Alternatively, a board geometry for an `Arrangement` of the four quarters of
+//! the board is written as five space-separated fields, such as
+//! `0,0 0,2 0,4 0,6 3` for a 2×8 strip:
+//!
+//! 1. The top left square of each quarter as a row and column separated by a
+//!    comma, in order of quarter.
+//! 2. The length of the lines of squares that score.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::arrangement::Arrangement;
+use crate::board::Board;
+use crate::game::{Game, MoveError};
+use crate::geometry::BoardGeometry;
+use crate::hand::Hand;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::tile::Tile::{self, *};
+
+/// The token representing a pass.
+const PASS: &str = "pass";
+
+/// The letter representing an occupied square in a position when the tile is
+/// not known.
+const UNKNOWN_TILE: char = 'X';
+
+/// Represents an error in parsing notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    /// A move was empty.
+    EmptyMove,
+
+    /// A tile letter was not recognised.
+    UnknownTile(String),
+
+    /// A square was not on the board.
+    InvalidSquare(String),
+
+    /// A position was not in the correct format. The field contains a
+    /// description of the problem.
+    InvalidPosition(String),
+
+    /// A board geometry was not in the correct format. The field contains a
+    /// description of the problem.
+    InvalidGeometry(String),
+
+    /// A move in a game record could not be played. The first field is the
+    /// index of the move within the record.
+    IllegalMove(usize, Move, MoveError),
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::EmptyMove => f.write_str("empty move"),
+            NotationError::UnknownTile(tile) => write!(f, "unknown tile '{tile}'"),
+            NotationError::InvalidSquare(square) => write!(f, "invalid square '{square}'"),
+            NotationError::InvalidPosition(problem) => write!(f, "invalid position: {problem}"),
+            NotationError::InvalidGeometry(problem) => write!(f, "invalid board: {problem}"),
+            NotationError::IllegalMove(index, player_move, error) => {
+                write!(
+                    f,
+                    "illegal move '{player_move}' at move {}: {error}",
+                    index + 1
+                )
+            }
+        }
+    }
+}
+
+impl Error for NotationError {}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Play(tile, square) => {
+                write!(f, "{} {}", format_tile(*tile), format_square(*square))
+            }
+            Move::Pass => f.write_str(PASS),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.eq_ignore_ascii_case(PASS) {
+            return Ok(Move::Pass);
+        }
+
+        let mut chars = s.chars();
+        let tile = chars.next().ok_or(NotationError::EmptyMove)?;
+
+        Ok(Move::Play(
+            parse_tile(tile)?,
+            parse_square(chars.as_str().trim_start())?,
+        ))
+    }
+}
+
+impl Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tile in Tile::ALL {
+            write!(f, "{}", self.count(tile))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Hand {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let counts = s
+            .chars()
+            .map(|count| count.to_digit(10).map(|count| count as u8))
+            .collect::<Option<Vec<u8>>>()
+            .and_then(|counts| <[u8; 4]>::try_from(counts).ok())
+            .ok_or_else(|| NotationError::InvalidPosition(format!("invalid hand '{s}'")))?;
+
+        Hand::try_new(counts).ok_or_else(|| {
+            NotationError::InvalidPosition(format!(
+                "hand '{s}' holds more than {} tiles",
+                Hand::MAX_TILES
+            ))
+        })
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_position(f, &Position::from(*self))
+    }
+}
+
+impl FromStr for Game {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(|position: Position| position.get_game())
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_position(f, self)?;
+        write!(
+            f,
+            " {}",
+            if self.is_second_player_to_move() {
+                2
+            } else {
+                1
+            }
+        )
+    }
+}
+
+impl FromStr for Position {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = NotationError::InvalidPosition;
+        let fields: Vec<_> = s.split_whitespace().collect();
+
+        let (rows, hands, unavailable, second_player_to_move) = match fields[..] {
+            [rows, first_hand, second_hand, unavailable] => {
+                (rows, [first_hand, second_hand], unavailable, false)
+            }
+            [rows, first_hand, second_hand, unavailable, player] => (
+                rows,
+                [first_hand, second_hand],
+                unavailable,
+                match player {
+                    "1" => false,
+                    "2" => true,
+                    _ => return Err(invalid(format!("invalid player to move '{player}'"))),
+                },
+            ),
+            _ => {
+                return Err(invalid(format!(
+                    "expected 4 or 5 fields, found {}",
+                    fields.len()
+                )))
+            }
+        };
+
+        let squares: Vec<&str> = rows.split('/').collect();
+
+        if squares.len() != 4 || squares.iter().any(|row| row.chars().count() != 4) {
+            return Err(invalid(format!("invalid board '{rows}'")));
+        }
+
+        let mut boards = [Board::default(); 2];
+        let mut tiles = [None; 16];
+
+        for (square, letter) in squares.concat().chars().enumerate() {
+            if letter == '.' {
+                continue;
+            }
+
+            if !letter.eq_ignore_ascii_case(&UNKNOWN_TILE) {
+                tiles[square] = Some(parse_tile(letter)?);
+            }
+
+            let player = usize::from(letter.is_ascii_lowercase());
+            boards[player] = boards[player].with(square as u8);
+        }
+
+        let [first_hand, second_hand] = hands.map(str::parse::<Hand>);
+        let mut hands = [first_hand?, second_hand?];
+
+        let unavailable = u16::from_str_radix(unavailable, 16)
+            .map_err(|_| invalid(format!("invalid unavailable squares '{unavailable}'")))?;
+
+        // The game state is stored from the perspective of the player to move.
+        if second_player_to_move {
+            boards.swap(0, 1);
+            hands.swap(0, 1);
+        }
+
+        Ok(Position::new(
+            Game::new(
+                boards[0],
+                hands[0],
+                boards[1],
+                hands[1],
+                Board::from(unavailable),
+            ),
+            tiles,
+            second_player_to_move,
+        ))
+    }
+}
+
+/// Writes the first four fields of the notation for the specified position.
+fn write_position(f: &mut fmt::Formatter<'_>, position: &Position) -> fmt::Result {
+    let game = position.get_game();
+
+    let mut boards = [game.get_board(), game.get_opponent_board()];
+    let mut hands = [game.get_hand(), game.get_opponent_hand()];
+
+    if position.is_second_player_to_move() {
+        boards.swap(0, 1);
+        hands.swap(0, 1);
+    }
+
+    for square in 0..16 {
+        if square > 0 && square % 4 == 0 {
+            f.write_str("/")?;
+        }
+
+        let letter = position.get_tile(square).map_or(UNKNOWN_TILE, format_tile);
+
+        write!(
+            f,
+            "{}",
+            if !boards[0].is_available(square) {
+                letter
+            } else if !boards[1].is_available(square) {
+                letter.to_ascii_lowercase()
+            } else {
+                '.'
+            }
+        )?;
+    }
+
+    write!(
+        f,
+        " {} {} {:04x}",
+        hands[0],
+        hands[1],
+        u16::from(game.get_unavailable())
+    )
+}
+
+impl FromStr for BoardGeometry {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = NotationError::InvalidGeometry;
+        let fields: Vec<_> = s.split_whitespace().collect();
+
+        match fields[..] {
+            [playable, line_length] => {
+                let playable = u16::from_str_radix(playable, 16)
+                    .map_err(|_| invalid(format!("invalid playable squares '{playable}'")))?;
+
+                Ok(BoardGeometry::new(
+                    Board::from(playable),
+                    parse_line_length(line_length, 4)?,
+                ))
+            }
+            [first, second, third, fourth, line_length] => {
+                let mut quarters = [(0, 0); 4];
+
+                for (quarter, field) in quarters.iter_mut().zip([first, second, third, fourth]) {
+                    *quarter = field
+                        .split_once(',')
+                        .and_then(|(row, column)| Some((row.parse().ok()?, column.parse().ok()?)))
+                        .ok_or_else(|| invalid(format!("invalid quarter '{field}'")))?;
+                }
+
+                // The bottom right square of each quarter must have a row and
+                // column that fit in an `i8`.
+                if let Some(field) = [first, second, third, fourth]
+                    .into_iter()
+                    .zip(quarters)
+                    .find_map(|(field, (row, column))| {
+                        (row == i8::MAX || column == i8::MAX).then_some(field)
+                    })
+                {
+                    return Err(invalid(format!("quarter '{field}' is out of range")));
+                }
+
+                let arrangement = Arrangement::new(quarters)
+                    .ok_or_else(|| invalid("the quarters overlap".to_string()))?;
+
+                Ok(arrangement.geometry(parse_line_length(line_length, 8)?))
+            }
+            _ => Err(invalid(format!(
+                "expected 2 or 5 fields, found {}",
+                fields.len()
+            ))),
+        }
+    }
+}
+
+/// Returns the line length represented by the specified field of a board
+/// geometry, which must be at least 2 and at most the specified maximum.
+fn parse_line_length(s: &str, max: u8) -> Result<u8, NotationError> {
+    s.parse()
+        .ok()
+        .filter(|line_length| (2..=max).contains(line_length))
+        .ok_or_else(|| NotationError::InvalidGeometry(format!("invalid line length '{s}'")))
+}
+
+/// Returns the letter representing the specified tile.
+pub fn format_tile(tile: Tile) -> char {
+    match tile {
+        Puller => 'L',
+        Pusher => 'P',
+        Straight => 'S',
+        Diagonal => 'D',
+    }
+}
+
+/// Returns the tile represented by the specified letter, in either case.
+pub fn parse_tile(letter: char) -> Result<Tile, NotationError> {
+    match letter.to_ascii_uppercase() {
+        'L' => Ok(Puller),
+        'P' => Ok(Pusher),
+        'S' => Ok(Straight),
+        'D' => Ok(Diagonal),
+        _ => Err(NotationError::UnknownTile(letter.to_string())),
+    }
+}
+
+/// Returns the notation for the specified square.
+pub fn format_square(square: u8) -> String {
+    format!("{}{}", char::from(b'a' + square % 4), square / 4 + 1)
+}
+
+/// Returns the square represented by the specified notation.
+pub fn parse_square(s: &str) -> Result<u8, NotationError> {
+    match s.to_ascii_lowercase().as_bytes() {
+        [column @ b'a'..=b'd', row @ b'1'..=b'4'] => Ok((row - b'1') * 4 + (column - b'a')),
+        _ => Err(NotationError::InvalidSquare(s.to_string())),
+    }
+}
+
+/// Returns the game record for the specified moves.
+pub fn format_record(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the moves in the specified game record, checking that each move can
+/// be played in turn from `Game::initial()`.
+pub fn parse_record(record: &str) -> Result<Vec<Move>, NotationError> {
+    let moves = record
+        .split([',', '\n'])
+        .filter(|token| !token.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Move>, _>>()?;
+
+    replay(Game::initial(), &moves)?;
+
+    Ok(moves)
+}
+
+/// Returns the game state after playing the specified moves in turn from the
+/// specified game state, or an error for the first move that cannot be played.
+pub fn replay(game: Game, moves: &[Move]) -> Result<Game, NotationError> {
+    moves
+        .iter()
+        .enumerate()
+        .try_fold(game, |game, (index, &player_move)| {
+            game.try_after(player_move)
+                .map_err(|error| NotationError::IllegalMove(index, player_move, error))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fmt() {
+        assert_eq!("P b2", Move::Play(Pusher, 5).to_string());
+        assert_eq!("L d4", Move::Play(Puller, 15).to_string());
+        assert_eq!("pass", Move::Pass.to_string());
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Ok(Move::Play(Pusher, 5)), "P b2".parse());
+        assert_eq!(Ok(Move::Play(Straight, 12)), " sa4 ".parse());
+        assert_eq!(Ok(Move::Pass), "PASS".parse::<Move>());
+
+        assert_eq!(Err(NotationError::EmptyMove), "".parse::<Move>());
+        assert_eq!(
+            Err(NotationError::UnknownTile("X".to_string())),
+            "X a1".parse::<Move>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidSquare("e1".to_string())),
+            "D e1".parse::<Move>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidSquare("a5".to_string())),
+            "D a5".parse::<Move>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidSquare("".to_string())),
+            "D".parse::<Move>()
+        );
+    }
+
+    #[test]
+    fn format_tile_and_parse_tile() {
+        for tile in Tile::ALL {
+            assert_eq!(Ok(tile), parse_tile(format_tile(tile)));
+        }
+    }
+
+    #[test]
+    fn format_square_and_parse_square() {
+        assert_eq!("a1", format_square(0));
+        assert_eq!("d1", format_square(3));
+        assert_eq!("c3", format_square(10));
+
+        for square in 0..16 {
+            assert_eq!(Ok(square), parse_square(&format_square(square)));
+        }
+    }
+
+    #[test]
+    fn format_record_and_parse_record() {
+        let record = "S a1, S d1, S d4, S a4, P a2, L c3";
+        let moves = parse_record(record).unwrap();
+
+        assert_eq!(
+            vec![
+                Move::Play(Straight, 0),
+                Move::Play(Straight, 3),
+                Move::Play(Straight, 15),
+                Move::Play(Straight, 12),
+                Move::Play(Pusher, 4),
+                Move::Play(Puller, 10),
+            ],
+            moves
+        );
+        assert_eq!(record, format_record(&moves));
+        assert_eq!(Ok(moves), parse_record("sa1\nsd1\n\nsd4, sa4,pa2,\nlc3"));
+    }
+
+    #[test]
+    fn parse_record_errors() {
+        assert_eq!(
+            Err(NotationError::UnknownTile("Q".to_string())),
+            parse_record("S a1, Q b1")
+        );
+
+        // The central squares are unavailable for the first move.
+        assert_eq!(
+            Err(NotationError::IllegalMove(
+                0,
+                Move::Play(Pusher, 5),
+                MoveError::Unavailable(5)
+            )),
+            parse_record("P b2")
+        );
+
+        // A Straight only allows moves in the same row or column.
+        assert_eq!(
+            Err(NotationError::IllegalMove(
+                1,
+                Move::Play(Straight, 15),
+                MoveError::Unavailable(15)
+            )),
+            parse_record("S a1, S d4")
+        );
+
+        // Passing is only allowed when no moves are available.
+        assert_eq!(
+            Err(NotationError::IllegalMove(
+                1,
+                Move::Pass,
+                MoveError::PassNotAllowed
+            )),
+            parse_record("S a1, pass")
+        );
+
+        // Each player only has two of each tile.
+        assert_eq!(
+            Err(NotationError::IllegalMove(
+                4,
+                Move::Play(Straight, 8),
+                MoveError::NotInHand(Straight)
+            )),
+            parse_record("S a1, S d1, S d4, S a4, S a3")
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        // A Puller in the corner only allows moves in squares 1, 4, and 5,
+        // which are occupied, so the next player must pass.
+        let game = Game::initial()
+            .with_move(Pusher, 1)
+            .with_move(Pusher, 4)
+            .with_move(Diagonal, 5)
+            .with_move(Puller, 0);
+
+        assert_eq!(
+            Ok(game.with_pass().with_move(Straight, 15)),
+            replay(game, &[Move::Pass, Move::Play(Straight, 15)])
+        );
+        assert_eq!(
+            Err(NotationError::IllegalMove(
+                0,
+                Move::Play(Straight, 15),
+                MoveError::Unavailable(15)
+            )),
+            replay(game, &[Move::Play(Straight, 15)])
+        );
+    }
+
+    #[test]
+    fn fmt_game() {
+        assert_eq!(
+            "..../..../..../.... 2222 2222 fffc",
+            Game::default().to_string()
+        );
+        assert_eq!(
+            "..../..../..../.... 2222 2222 0660",
+            Game::initial().to_string()
+        );
+        assert_eq!(
+            "X..x/..../..../.... 2212 2212 7779",
+            Game::initial()
+                .with_move(Straight, 0)
+                .with_move(Straight, 3)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn from_str_game() {
+        let game = Game::initial()
+            .with_move(Straight, 0)
+            .with_move(Straight, 3)
+            .with_move(Pusher, 15);
+
+        assert_eq!(Ok(game), game.to_string().parse());
+        assert_eq!(Ok(game), "s..S/..../..../...p 2212 2112 cc09".parse());
+        assert_eq!(
+            Ok(Game::default()),
+            " ..../..../..../....  2222\t2222 FFFC ".parse()
+        );
+
+        // Occupied squares are always unavailable.
+        assert_eq!(Ok(game), "x..X/..../..../...x 2212 2112 4c00".parse());
+
+        // The board and hands are from the perspective of the first player
+        // when the player to move is given.
+        assert_eq!(Ok(game), "S..s/..../..../...P 2112 2212 cc09 2".parse());
+        assert_eq!(Ok(game), "s..S/..../..../...p 2212 2112 cc09 1".parse());
+    }
+
+    #[test]
+    fn fmt_position() {
+        let position = Position::from(Game::initial())
+            .try_after(Move::Play(Straight, 0))
+            .and_then(|position| position.try_after(Move::Play(Straight, 3)))
+            .and_then(|position| position.try_after(Move::Play(Pusher, 15)))
+            .unwrap();
+
+        assert_eq!("S..s/..../..../...P 2112 2212 cc09 2", position.to_string());
+        assert_eq!(
+            "x..X/..../..../...x 2212 2112 cc09",
+            position.get_game().to_string()
+        );
+        assert_eq!(
+            "..../..../..../.... 2222 2222 0660 1",
+            Position::from(Game::initial()).to_string()
+        );
+    }
+
+    #[test]
+    fn from_str_position() {
+        let position: Position = "S..s/..../..../...P 2112 2212 cc09 2".parse().unwrap();
+        assert!(position.is_second_player_to_move());
+        assert_eq!(Some(Straight), position.get_tile(0));
+        assert_eq!(Some(Pusher), position.get_tile(15));
+        assert_eq!(None, position.get_tile(1));
+        assert_eq!(Ok(position), position.to_string().parse());
+
+        // Tiles that are not known are written as `X`.
+        let position: Position = "X..s/..../..../.... 2212 2212 7779".parse().unwrap();
+        assert!(!position.is_second_player_to_move());
+        assert_eq!(None, position.get_tile(0));
+        assert_eq!(Some(Straight), position.get_tile(3));
+        assert_eq!("X..s/..../..../.... 2212 2212 7779 1", position.to_string());
+
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "invalid player to move '3'".to_string()
+            )),
+            "..../..../..../.... 2222 2222 0660 3".parse::<Position>()
+        );
+    }
+
+    #[test]
+    fn from_str_game_errors() {
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "expected 4 or 5 fields, found 3".to_string()
+            )),
+            "..../..../..../.... 2222 2222".parse::<Game>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "invalid board '..../..../......'".to_string()
+            )),
+            "..../..../...... 2222 2222 fffc".parse::<Game>()
+        );
+        assert_eq!(
+            Err(NotationError::UnknownTile("q".to_string())),
+            "q.../..../..../.... 2222 2222 fffc".parse::<Game>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "invalid hand '222'".to_string()
+            )),
+            "..../..../..../.... 222 2222 fffc".parse::<Game>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "hand '9990' holds more than 16 tiles".to_string()
+            )),
+            "..../..../..../.... 2222 9990 fffc".parse::<Game>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidPosition(
+                "invalid unavailable squares 'fffg'".to_string()
+            )),
+            "..../..../..../.... 2222 2222 fffg".parse::<Game>()
+        );
+    }
+
+    #[test]
+    fn from_str_geometry() {
+        assert_eq!(
+            Ok(BoardGeometry::new(Board::from(0x0777), 3)),
+            "0777 3".parse()
+        );
+        assert_eq!(
+            Ok(BoardGeometry::new(Board::from(u16::MAX), 4)),
+            " FFFF\t4 ".parse()
+        );
+
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "expected 2 or 5 fields, found 1".to_string()
+            )),
+            "0777".parse::<BoardGeometry>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "invalid playable squares '077g'".to_string()
+            )),
+            "077g 3".parse::<BoardGeometry>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "invalid line length '5'".to_string()
+            )),
+            "0777 5".parse::<BoardGeometry>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "invalid line length '1'".to_string()
+            )),
+            "0777 1".parse::<BoardGeometry>()
+        );
+    }
+
+    #[test]
+    fn from_str_arrangement_geometry() {
+        assert_eq!(
+            Ok(Arrangement::STRIP.geometry(8)),
+            "0,0 0,2 0,4 0,6 8".parse()
+        );
+        assert_eq!(
+            Ok(Arrangement::new([(-1, 0), (0, 2), (2, 0), (2, 2)])
+                .unwrap()
+                .geometry(3)),
+            "-1,0 0,2 2,0 2,2 3".parse()
+        );
+
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "invalid quarter '0;2'".to_string()
+            )),
+            "0,0 0;2 0,4 0,6 3".parse::<BoardGeometry>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "the quarters overlap".to_string()
+            )),
+            "0,0 0,1 0,4 0,6 3".parse::<BoardGeometry>()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "quarter '127,0' is out of range".to_string()
+            )),
+            "127,0 0,2 0,4 0,6 3".parse::<BoardGeometry>()
+        );
+
+        // Lines that would run beyond the range of an `i8` do not score.
+        assert_eq!(
+            Ok(Arrangement::new([(126, 0), (126, 2), (126, 4), (126, 6)])
+                .unwrap()
+                .geometry(3)),
+            "126,0 126,2 126,4 126,6 3".parse()
+        );
+        assert_eq!(
+            Err(NotationError::InvalidGeometry(
+                "invalid line length '9'".to_string()
+            )),
+            "0,0 0,2 0,4 0,6 9".parse::<BoardGeometry>()
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Provides a function for solving Mijnlieff using multiple threads.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::game::Game;
+use crate::limits::{Limits, SolveOutcome};
+use crate::moves::Move;
+use crate::progress::Progress;
+use crate::solver::{self, SolveHook};
+use crate::status::Status::{self, *};
+
+/// The number of moves from the initial position after which the remaining
+/// subtrees are solved as separate tasks.
+///
+/// Splitting more deeply creates more tasks, which balances the work between
+/// threads more evenly and allows more work to be skipped once a winning move
+/// has been found, at the cost of more synchronisation.
+const SPLIT_DEPTH: u32 = 3;
+
+/// Solves Mijnlieff from a specified game position using the specified number
+/// of threads.
+///
+/// The game tree is split into tasks a few moves from the specified position,
+/// which the threads solve in order of preference. When a winning move is found
+/// for a position, tasks for any less preferred moves from that position are
+/// cancelled, including those already being solved.
+///
+/// The status is the same as that returned by `solver::solve`, and the second
+/// parameter is updated with the same count of the number of games analysed,
+/// as games analysed in cancelled tasks are not counted.
+pub fn solve_parallel(game: Game, threads: usize, games: &mut u64) -> Status {
+    solve_parallel_with_limits(game, threads, &Limits::default(), games)
+        .status()
+        .expect("the solve has no limits")
+}
+
+/// Solves Mijnlieff from a specified game position in the same way as
+/// `solve_parallel`, stopping once any of the specified limits is reached.
+///
+/// The limits are checked by each thread every 65,536 games, and before the
+/// budget would be exceeded by the games analysed by every thread, so the
+/// budget is exact on a single thread but may be exceeded by up to 65,536
+/// games per thread with more threads.
+///
+/// If the solve finishes, the outcome and the count of games are the same as
+/// for `solve_parallel`. Otherwise the outcome contains the status of each move
+/// from the position that was solved before stopping, in order of preference,
+/// and the count includes every game analysed.
+pub fn solve_parallel_with_limits(
+    game: Game,
+    threads: usize,
+    limits: &Limits,
+    games: &mut u64,
+) -> SolveOutcome {
+    solve_parallel_with_progress(game, threads, limits, games, Duration::MAX, |_| ())
+}
+
+/// Solves Mijnlieff from a specified game position in the same way as
+/// `solve_parallel_with_limits`, calling the specified function with the
+/// progress of the solve at most once per interval.
+///
+/// The function is called on the calling thread, which waits for the other
+/// threads. The count of games in the progress is updated by each thread every
+/// 65,536 games, and includes games analysed in cancelled tasks, and the
+/// current move is the most preferred move whose status is not yet known.
+pub fn solve_parallel_with_progress(
+    game: Game,
+    threads: usize,
+    limits: &Limits,
+    games: &mut u64,
+    interval: Duration,
+    mut report: impl FnMut(&Progress),
+) -> SolveOutcome {
+    let threads = threads.max(1);
+    let start = Instant::now();
+    let mut tree = Tree::new(game, start);
+
+    let tasks: Vec<(usize, Game)> = tree
+        .tasks
+        .iter()
+        .map(|&node| (node, tree.nodes[node].game))
+        .collect();
+    let cancelled: Vec<AtomicBool> = tasks.iter().map(|_| AtomicBool::new(false)).collect();
+    let next_task = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    let analysed = AtomicU64::new(0);
+    let finished = AtomicUsize::new(0);
+
+    tree.resolve_all();
+    let tree = Mutex::new(tree);
+
+    thread::scope(|scope| {
+        let caller = thread::current();
+
+        for _ in 0..threads {
+            let caller = caller.clone();
+            let (tasks, cancelled, tree) = (&tasks, &cancelled, &tree);
+            let (next_task, stopped, analysed, finished) =
+                (&next_task, &stopped, &analysed, &finished);
+
+            scope.spawn(move || {
+                loop {
+                    let task = next_task.fetch_add(1, Ordering::Relaxed);
+
+                    let Some(&(node, task_game)) = tasks.get(task) else {
+                        break;
+                    };
+
+                    if limits.is_reached(analysed.load(Ordering::Relaxed)) {
+                        stopped.store(true, Ordering::Relaxed);
+                    }
+
+                    if stopped.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    if cancelled[task].load(Ordering::Relaxed) {
+                        continue;
+                    }
+
+                    let task_analysed = analysed.load(Ordering::Relaxed);
+                    let mut hook = TaskHook {
+                        limits,
+                        cancelled: &cancelled[task],
+                        stopped,
+                        analysed,
+                        checked_games: 0,
+                        next_check: limits.next_check(task_analysed) - task_analysed,
+                    };
+                    let mut task_games = 0;
+                    let status = solver::solve_hooked(task_game, &mut task_games, &mut hook);
+                    analysed.fetch_add(task_games - hook.checked_games, Ordering::Relaxed);
+
+                    if let Some(status) = status {
+                        let mut tree = tree.lock().unwrap();
+                        tree.nodes[node].result = Some((status, task_games));
+                        tree.propagate(node, cancelled);
+                    }
+                }
+
+                finished.fetch_add(1, Ordering::Release);
+                caller.unpark();
+            });
+        }
+
+        let mut last_report = start;
+
+        while finished.load(Ordering::Acquire) < threads {
+            match last_report.checked_add(interval) {
+                Some(next_report) => {
+                    thread::park_timeout(next_report.saturating_duration_since(Instant::now()))
+                }
+                None => thread::park(),
+            }
+
+            let now = Instant::now();
+
+            if now - last_report >= interval && finished.load(Ordering::Acquire) < threads {
+                last_report = now;
+                let tree = tree.lock().unwrap();
+                report(&tree.progress(analysed.load(Ordering::Relaxed), now - start));
+            }
+        }
+    });
+
+    let tree = tree.into_inner().unwrap();
+
+    match tree.nodes[0].result {
+        Some((status, tree_games)) => {
+            *games += tree_games;
+            SolveOutcome::Solved(status)
+        }
+        None => {
+            *games += analysed.into_inner();
+            SolveOutcome::Stopped(
+                tree.root_moves()
+                    .filter_map(|(player_move, child)| {
+                        tree.nodes[child]
+                            .result
+                            .map(|(status, _)| (player_move, !status))
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Stops a task once it is cancelled or a limit is reached, as a hook for
+/// `solver::solve_hooked`.
+struct TaskHook<'a> {
+    /// The limits of the solve.
+    limits: &'a Limits<'a>,
+
+    /// The flag cancelling the task.
+    cancelled: &'a AtomicBool,
+
+    /// The flag stopping every task once a limit is reached.
+    stopped: &'a AtomicBool,
+
+    /// The count of games analysed by every thread.
+    analysed: &'a AtomicU64,
+
+    /// The count of games in the task already added to the shared count.
+    checked_games: u64,
+
+    /// The count of games in the task at which the flags are next checked.
+    next_check: u64,
+}
+
+impl TaskHook<'_> {
+    /// Adds the games analysed since the last check to the shared count and
+    /// checks the limits and the flags, returning whether the task should stop.
+    #[cold]
+    fn check(&mut self, games: u64) -> bool {
+        let analysed = self
+            .analysed
+            .fetch_add(games - self.checked_games, Ordering::Relaxed)
+            + games
+            - self.checked_games;
+
+        self.checked_games = games;
+        self.next_check = games + self.limits.next_check(analysed) - analysed;
+
+        if self.limits.is_reached(analysed) {
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+
+        self.stopped.load(Ordering::Relaxed) || self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl SolveHook for TaskHook<'_> {
+    #[inline]
+    fn should_stop(&mut self, games: u64) -> bool {
+        games >= self.next_check && self.check(games)
+    }
+}
+
+/// Represents the game tree up to the depth at which it is split into tasks.
+struct Tree {
+    /// The nodes, in depth-first order with moves in order of preference, so
+    /// the root node is at index 0.
+    nodes: Vec<Node>,
+
+    /// The indices of the nodes to be solved as tasks, in depth-first order.
+    tasks: Vec<usize>,
+
+    /// The time at which the solve started.
+    start: Instant,
+
+    /// The time between the start of the solve and the last time the status
+    /// of a move from the root position became known.
+    completed_elapsed: Duration,
+}
+
+/// Represents a node in the game tree.
+struct Node {
+    /// The game position.
+    game: Game,
+
+    /// The index of the parent node, or `None` for the root node.
+    parent: Option<usize>,
+
+    /// The indices of the child nodes, in order of preference.
+    children: Vec<usize>,
+
+    /// The indices of the tasks within this node's subtree.
+    tasks: Range<usize>,
+
+    /// The victory status and the number of games analysed, once known.
+    result: Option<(Status, u64)>,
+}
+
+impl Tree {
+    /// Returns the tree for the specified game position, for a solve started
+    /// at the specified time.
+    fn new(game: Game, start: Instant) -> Self {
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            tasks: Vec::new(),
+            start,
+            completed_elapsed: Duration::ZERO,
+        };
+
+        tree.add(game, None, SPLIT_DEPTH);
+        tree
+    }
+
+    /// Adds the node for the specified game position, and its subtree, and
+    /// returns its index.
+    fn add(&mut self, game: Game, parent: Option<usize>, depth: u32) -> usize {
+        let index = self.nodes.len();
+        let first_task = self.tasks.len();
+
+        self.nodes.push(Node {
+            game,
+            parent,
+            children: Vec::new(),
+            tasks: first_task..first_task,
+            result: None,
+        });
+
+        if game.is_over() {
+            self.nodes[index].result = Some((game.get_status(), 1));
+        } else if depth == 0 {
+            self.tasks.push(index);
+        } else {
+            for player_move in solver::get_moves(&game) {
+                let child = self.add(game.after(player_move), Some(index), depth - 1);
+                self.nodes[index].children.push(child);
+            }
+        }
+
+        self.nodes[index].tasks.end = self.tasks.len();
+        index
+    }
+
+    /// Resolves every node whose result can be determined before any tasks
+    /// have been solved.
+    fn resolve_all(&mut self) {
+        // Children have higher indices than their parents, so iterating in
+        // reverse resolves children first.
+        for index in (0..self.nodes.len()).rev() {
+            if self.nodes[index].result.is_none() && !self.nodes[index].children.is_empty() {
+                self.resolve(index);
+            }
+        }
+    }
+
+    /// Updates the ancestors of a node whose result has just been determined,
+    /// cancelling any tasks that can no longer affect the result.
+    fn propagate(&mut self, mut index: usize, cancelled: &[AtomicBool]) {
+        while let Some(parent) = self.nodes[index].parent {
+            if parent == 0 {
+                self.completed_elapsed = self.start.elapsed();
+            }
+
+            // The parent has a winning move, so its less preferred moves need
+            // not be solved.
+            if let Some((Loss, _)) = self.nodes[index].result {
+                let tasks = self.nodes[index].tasks.end..self.nodes[parent].tasks.end;
+
+                for task in &cancelled[tasks] {
+                    task.store(true, Ordering::Relaxed);
+                }
+            }
+
+            if !self.resolve(parent) {
+                return;
+            }
+
+            index = parent;
+        }
+    }
+
+    /// Returns each move from the root position, in order of preference, with
+    /// the index of the node after it.
+    fn root_moves(&self) -> impl Iterator<Item = (Move, usize)> + '_ {
+        solver::get_moves(&self.nodes[0].game)
+            .into_iter()
+            .zip(self.nodes[0].children.iter().copied())
+    }
+
+    /// Returns the progress of the solve, with the specified count of games
+    /// analysed and time elapsed.
+    fn progress(&self, games: u64, elapsed: Duration) -> Progress {
+        let completed = |&(_, child): &(Move, usize)| self.nodes[child].result.is_some();
+
+        Progress {
+            root_moves: self.nodes[0].children.len(),
+            root_moves_completed: self.root_moves().filter(completed).count(),
+            current_move: self
+                .root_moves()
+                .find(|root_move| !completed(root_move))
+                .map(|(player_move, _)| player_move),
+            games,
+            elapsed,
+            completed_elapsed: self.completed_elapsed,
+        }
+    }
+
+    /// Determines the result of the specified node from its children in the
+    /// same way as `solver::solve`, returning whether the result is known.
+    fn resolve(&mut self, index: usize) -> bool {
+        // Assume a loss until we have found a better result.
+        let mut status = Loss;
+        let mut games = 0;
+
+        for &child in &self.nodes[index].children {
+            let Some((child_status, child_games)) = self.nodes[child].result else {
+                return false;
+            };
+
+            games += child_games;
+
+            match child_status {
+                Win => (),
+                Draw => status = Draw,
+                // A win can't be improved upon, so later children don't count.
+                Loss => {
+                    status = Win;
+                    break;
+                }
+            }
+        }
+
+        self.nodes[index].result = Some((status, games));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn test_solve_parallel() {
+        let game = create_game();
+
+        let mut games = 0;
+        let status = solver::solve(game, &mut games);
+
+        for threads in [1, 2, 4] {
+            let mut parallel_games = 0;
+            assert_eq!(status, solve_parallel(game, threads, &mut parallel_games));
+            assert_eq!(games, parallel_games);
+        }
+    }
+
+    #[test]
+    fn test_solve_parallel_endgame() {
+        let (_, variation) = solver::principal_variation(create_game(), &mut 0);
+
+        // Every position in the line of perfect play, including the final
+        // position in which the game is over.
+        let mut game = create_game();
+
+        for player_move in variation.into_iter().map(Some).chain([None]) {
+            let mut games = 0;
+            let mut parallel_games = 0;
+            assert_eq!(
+                solver::solve(game, &mut games),
+                solve_parallel(game, 2, &mut parallel_games)
+            );
+            assert_eq!(games, parallel_games);
+
+            if let Some(player_move) = player_move {
+                game = game.after(player_move);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_parallel_with_limits() {
+        let game = create_game();
+
+        let mut games = 0;
+        let status = solver::solve(game, &mut games);
+
+        assert_eq!(
+            SolveOutcome::Solved(status),
+            solve_parallel_with_limits(game, 2, &Limits::default(), &mut 0)
+        );
+
+        // With a single thread the budget is exact.
+        let mut limited_games = 0;
+        let SolveOutcome::Stopped(proven) = solve_parallel_with_limits(
+            game,
+            1,
+            &Limits::default().with_max_games(games / 2),
+            &mut limited_games,
+        ) else {
+            panic!("the solve should stop");
+        };
+        assert_eq!(games / 2, limited_games);
+
+        for (player_move, status) in proven {
+            assert_eq!(status, !solver::solve(game.after(player_move), &mut 0));
+        }
+
+        let cancelled = AtomicBool::new(true);
+        assert_eq!(
+            SolveOutcome::Stopped(Vec::new()),
+            solve_parallel_with_limits(
+                game,
+                2,
+                &Limits::default().with_cancel_flag(&cancelled),
+                &mut 0
+            )
+        );
+    }
+
+    #[test]
+    fn test_solve_parallel_with_progress() {
+        let game = create_game();
+        let mut reports = Vec::new();
+
+        let mut games = 0;
+        let mut parallel_games = 0;
+        assert_eq!(
+            SolveOutcome::Solved(solver::solve(game, &mut games)),
+            solve_parallel_with_progress(
+                game,
+                2,
+                &Limits::default(),
+                &mut parallel_games,
+                Duration::from_millis(1),
+                |progress| reports.push(*progress)
+            )
+        );
+        assert_eq!(games, parallel_games);
+
+        for progress in reports {
+            assert_eq!(solver::get_moves(&game).len(), progress.root_moves);
+            assert_eq!(
+                progress.root_moves_completed < progress.root_moves,
+                progress.current_move.is_some()
+            );
+            assert!(progress.completed_elapsed <= progress.elapsed);
+        }
+    }
+
+    // Creates a position after six moves.
+    fn create_game() -> Game {
+        Game::default()
+            .with_move(Pusher, 0)
+            .with_move(Pusher, 5)
+            .with_move(Straight, 3)
+            .with_move(Straight, 12)
+            .with_move(Diagonal, 10)
+            .with_move(Puller, 15)
+    }
+}
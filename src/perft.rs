@@ -0,0 +1,189 @@
+//! Provides a function for counting the nodes in the game tree.
+
+use std::collections::BTreeMap;
+
+use crate::game::Game;
+use crate::moves::Move;
+
+/// Represents the counts from enumerating the game tree to a given depth.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Perft {
+    /// The number of positions at each depth, with the specified position at
+    /// depth 0. Passes count as moves.
+    pub nodes: Vec<u64>,
+
+    /// The number of passes.
+    pub passes: u64,
+
+    /// The number of positions in which the game is over.
+    pub games: u64,
+
+    /// The number of games over with each final margin, from the perspective of
+    /// the player to move in the specified position.
+    pub margins: BTreeMap<i8, u64>,
+}
+
+/// Enumerates every legal line of play from a specified game position up to the
+/// specified depth, counting the positions reached.
+///
+/// Positions in which the game is over are not expanded further, so a depth
+/// large enough to reach the end of every game counts every complete game.
+pub fn perft(game: Game, depth: usize) -> Perft {
+    let mut perft = Perft {
+        nodes: vec![0; depth + 1],
+        ..Perft::default()
+    };
+
+    count(game, 0, depth, true, &mut perft);
+
+    // Remove the counts for depths beyond the end of every game.
+    while perft.nodes.last() == Some(&0) {
+        perft.nodes.pop();
+    }
+
+    perft
+}
+
+/// Recursively counts the positions reached from a specified game position at
+/// the specified depth.
+///
+/// The fourth parameter is whether the player to move is the player to move in
+/// the position from which the counting started.
+fn count(game: Game, depth: usize, max_depth: usize, is_root_player: bool, perft: &mut Perft) {
+    perft.nodes[depth] += 1;
+
+    if game.is_over() {
+        let margin = game.get_margin();
+
+        perft.games += 1;
+        *perft
+            .margins
+            .entry(if is_root_player { margin } else { -margin })
+            .or_default() += 1;
+
+        return;
+    }
+
+    if depth == max_depth {
+        return;
+    }
+
+    for player_move in game.legal_moves() {
+        if player_move == Move::Pass {
+            perft.passes += 1;
+        }
+
+        count(
+            game.after(player_move),
+            depth + 1,
+            max_depth,
+            !is_root_player,
+            perft,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation;
+    use crate::tile::Tile;
+
+    /// Returns the counts from enumerating the game tree to the specified
+    /// depth by trying every tile in every square, and passing, in every
+    /// position, which is independent of the move generation under test.
+    fn brute_force(game: Game, depth: usize) -> Perft {
+        fn count(
+            game: Game,
+            depth: usize,
+            max_depth: usize,
+            is_root_player: bool,
+            perft: &mut Perft,
+        ) {
+            if perft.nodes.len() <= depth {
+                perft.nodes.push(0);
+            }
+
+            perft.nodes[depth] += 1;
+
+            if game.is_over() {
+                let margin = game.get_margin();
+
+                perft.games += 1;
+                *perft
+                    .margins
+                    .entry(if is_root_player { margin } else { -margin })
+                    .or_default() += 1;
+
+                return;
+            }
+
+            if depth == max_depth {
+                return;
+            }
+
+            let moves = (0..16)
+                .flat_map(|square| Tile::ALL.map(|tile| Move::Play(tile, square)))
+                .chain([Move::Pass]);
+
+            for player_move in moves {
+                if let Ok(next) = game.try_after(player_move) {
+                    if player_move == Move::Pass {
+                        perft.passes += 1;
+                    }
+
+                    count(next, depth + 1, max_depth, !is_root_player, perft);
+                }
+            }
+        }
+
+        let mut perft = Perft::default();
+        count(game, 0, depth, true, &mut perft);
+
+        perft
+    }
+
+    #[test]
+    fn test_perft() {
+        let perft = perft(Game::initial(), 3);
+        assert_eq!(brute_force(Game::initial(), 3), perft);
+
+        assert_eq!(vec![1, 48, 1152, 26272], perft.nodes);
+        assert_eq!(0, perft.passes);
+        assert_eq!(0, perft.games);
+        assert!(perft.margins.is_empty());
+    }
+
+    #[test]
+    fn test_perft_endgame() {
+        let game = notation::replay(
+            Game::initial(),
+            &notation::parse_record("S a1, S d1, S d4, S a4, P a2, L c3, P c4, D b1").unwrap(),
+        )
+        .unwrap();
+
+        let perft = perft(game, 16);
+        assert_eq!(brute_force(game, 16), perft);
+
+        assert_eq!(
+            vec![1, 4, 30, 146, 744, 2224, 5752, 8909, 13061, 7024, 2307, 286],
+            perft.nodes
+        );
+        assert_eq!(9043, perft.passes);
+        assert_eq!(14241, perft.games);
+        assert_eq!(
+            BTreeMap::from([
+                (-4, 24),
+                (-3, 383),
+                (-2, 1833),
+                (-1, 2138),
+                (0, 3464),
+                (1, 4456),
+                (2, 1660),
+                (3, 267),
+                (4, 16)
+            ]),
+            perft.margins
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Defines a struct representing a position as the players see it.
+
+use crate::game::{Game, MoveError};
+use crate::moves::Move;
+use crate::tile::Tile;
+
+/// Represents a position as the players see it: the game state, together with
+/// the tile occupying each square and which player is to move.
+///
+/// `Game` only stores the state necessary to solve the game, which includes
+/// neither of these, so they are stored alongside it. The `Display` and
+/// `FromStr` traits are implemented in the `notation` module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// The game state.
+    game: Game,
+
+    /// The tile occupying each square, or `None` if the square is unoccupied
+    /// or its tile is not known.
+    tiles: [Option<Tile>; 16],
+
+    /// Whether the second player is to move.
+    second_player_to_move: bool,
+}
+
+impl From<Game> for Position {
+    /// Returns the position for the specified game state with the first player
+    /// to move, and with the tile occupying each square not known.
+    fn from(game: Game) -> Self {
+        Position::new(game, [None; 16], false)
+    }
+}
+
+impl Position {
+    /// Returns an instance with the specified state.
+    pub fn new(game: Game, tiles: [Option<Tile>; 16], second_player_to_move: bool) -> Self {
+        Position {
+            game,
+            tiles,
+            second_player_to_move,
+        }
+    }
+
+    /// Returns the game state.
+    pub fn get_game(&self) -> Game {
+        self.game
+    }
+
+    /// Returns the tile occupying the specified square, or `None` if the
+    /// square is unoccupied or its tile is not known.
+    pub fn get_tile(&self, square: u8) -> Option<Tile> {
+        self.tiles[usize::from(square)]
+    }
+
+    /// Returns whether the second player is to move.
+    pub fn is_second_player_to_move(&self) -> bool {
+        self.second_player_to_move
+    }
+
+    /// Returns the position after the specified move, or an error if the move
+    /// is not allowed, as described for `Game::try_after`.
+    pub fn try_after(&self, player_move: Move) -> Result<Self, MoveError> {
+        let game = self.game.try_after(player_move)?;
+        let mut tiles = self.tiles;
+
+        if let Move::Play(tile, square) = player_move {
+            tiles[usize::from(square)] = Some(tile);
+        }
+
+        Ok(Position::new(game, tiles, !self.second_player_to_move))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+    use crate::game::{Game, MoveError};
+    use crate::moves::Move;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn try_after() {
+        let position = Position::from(Game::initial());
+        assert!(!position.is_second_player_to_move());
+
+        let position = position.try_after(Move::Play(Straight, 0)).unwrap();
+        assert!(position.is_second_player_to_move());
+        assert_eq!(Some(Straight), position.get_tile(0));
+        assert_eq!(None, position.get_tile(3));
+        assert_eq!(Game::initial().with_move(Straight, 0), position.get_game());
+
+        assert_eq!(
+            Err(MoveError::Unavailable(15)),
+            position.try_after(Move::Play(Straight, 15))
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Defines a struct describing the progress of a solve.
+
+use std::time::{Duration, Instant};
+
+use crate::limits::CHECK_GAMES;
+use crate::moves::Move;
+use crate::solver::SolveHook;
+
+/// Describes the progress of `solver::solve_with_progress` or
+/// `parallel::solve_parallel_with_progress`.
+///
+/// Progress is measured by the moves from the position being solved (the
+/// root moves) whose subtrees have been completed. Subtrees vary greatly in
+/// size, and a winning move ends the solve early, so the estimate of the time
+/// remaining is only a rough guide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The number of root moves.
+    pub root_moves: usize,
+
+    /// The number of root moves whose subtrees have been solved.
+    pub root_moves_completed: usize,
+
+    /// The root move whose subtree is being solved, if any.
+    pub current_move: Option<Move>,
+
+    /// The number of games analysed so far.
+    pub games: u64,
+
+    /// The time since the solve started.
+    pub elapsed: Duration,
+
+    /// The time between the start of the solve and the completion of the last
+    /// root move's subtree.
+    pub completed_elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the number of games analysed per second so far.
+    pub fn games_per_second(&self) -> f64 {
+        self.games as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Returns the estimated time until every root move has been solved,
+    /// assuming the remaining subtrees take as long on average as those
+    /// completed, or `None` if no subtree has been completed.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        if self.root_moves_completed == 0 {
+            return None;
+        }
+
+        let remaining = self.root_moves - self.root_moves_completed;
+        let total = self
+            .completed_elapsed
+            .mul_f64(remaining as f64 / self.root_moves_completed as f64);
+
+        // Time spent on the current subtree counts towards the remaining time.
+        Some(total.saturating_sub(self.elapsed - self.completed_elapsed))
+    }
+}
+
+/// Calls a function with the progress of a solve at most once per interval, as
+/// a hook for `solver::solve_hooked`.
+pub(crate) struct Reporter<F: FnMut(&Progress)> {
+    /// The progress at the last check.
+    progress: Progress,
+
+    /// The time at which the solve started.
+    start: Instant,
+
+    /// The minimum time between calls.
+    interval: Duration,
+
+    /// The time of the last call, or of the start if there has been none.
+    last_report: Instant,
+
+    /// The count of games at which the time is next checked.
+    next_check: u64,
+
+    /// The count of games when the solve started.
+    start_games: u64,
+
+    /// The function called with the progress.
+    report: F,
+}
+
+impl<F: FnMut(&Progress)> Reporter<F> {
+    /// Returns a reporter for a solve with the specified number of root moves,
+    /// starting with the specified count of games.
+    pub(crate) fn new(root_moves: usize, games: u64, interval: Duration, report: F) -> Self {
+        let start = Instant::now();
+
+        Reporter {
+            progress: Progress {
+                root_moves,
+                root_moves_completed: 0,
+                current_move: None,
+                games: 0,
+                elapsed: Duration::ZERO,
+                completed_elapsed: Duration::ZERO,
+            },
+            start,
+            interval,
+            last_report: start,
+            next_check: games + CHECK_GAMES,
+            start_games: games,
+            report,
+        }
+    }
+
+    /// Checks the time, calling the function if the interval has passed since
+    /// the last call.
+    #[cold]
+    fn check(&mut self, games: u64) {
+        self.next_check = games + CHECK_GAMES;
+
+        let now = Instant::now();
+
+        if now - self.last_report >= self.interval {
+            self.last_report = now;
+            self.progress.games = games - self.start_games;
+            self.progress.elapsed = now - self.start;
+            (self.report)(&self.progress);
+        }
+    }
+}
+
+impl<F: FnMut(&Progress)> SolveHook for Reporter<F> {
+    #[inline]
+    fn should_stop(&mut self, games: u64) -> bool {
+        if games >= self.next_check {
+            self.check(games);
+        }
+
+        false
+    }
+
+    fn start_move(&mut self, player_move: Move) {
+        self.progress.current_move = Some(player_move);
+    }
+
+    fn finish_move(&mut self) {
+        self.progress.current_move = None;
+        self.progress.root_moves_completed += 1;
+        self.progress.completed_elapsed = self.start.elapsed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimated_remaining() {
+        let mut progress = Progress {
+            root_moves: 8,
+            root_moves_completed: 0,
+            current_move: None,
+            games: 1000,
+            elapsed: Duration::from_secs(10),
+            completed_elapsed: Duration::ZERO,
+        };
+
+        assert_eq!(None, progress.estimated_remaining());
+        assert_eq!(100.0, progress.games_per_second());
+
+        progress.root_moves_completed = 2;
+        progress.completed_elapsed = Duration::from_secs(8);
+        assert_eq!(
+            Some(Duration::from_secs(22)),
+            progress.estimated_remaining()
+        );
+
+        progress.elapsed = Duration::from_secs(100);
+        assert_eq!(Some(Duration::ZERO), progress.estimated_remaining());
+    }
+}
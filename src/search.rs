@@ -0,0 +1,398 @@
+//! Provides a heuristic search for boards too large to solve.
+
+use crate::limits::{Limiter, Limits};
+use crate::moves::Move;
+use crate::solver::{SolveHook, TILES_PREFERENCE};
+use crate::wide::{WideGame, WideGeometry};
+
+/// The score of a position in which the game is over and the current player
+/// has won, before adding the weighted margin, which is larger than the
+/// evaluation of any position in which the game is not over.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// The weight of each point of the margin in an evaluation.
+const MARGIN_WEIGHT: i32 = 100;
+
+/// The weight of each tile in a line that could still be completed.
+const LINE_WEIGHT: i32 = 10;
+
+/// The weight of each tile by which the current player's hand is larger.
+const TILE_WEIGHT: i32 = 5;
+
+/// A score larger than any evaluation, which is used as the initial search
+/// window.
+const INFINITY: i32 = i32::MAX;
+
+/// Represents the result of a heuristic search.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The best move found, or `None` if the game is over.
+    pub best_move: Option<Move>,
+
+    /// The score of the position for the current player, which is at least
+    /// `WIN_SCORE` for a proven win and at most `-WIN_SCORE` for a proven loss.
+    pub score: i32,
+
+    /// The number of moves searched ahead.
+    pub depth: u32,
+
+    /// The number of positions visited.
+    pub nodes: u64,
+
+    /// Whether every line of play searched reached the end of the game, in
+    /// which case the score is exact rather than an estimate.
+    pub exact: bool,
+}
+
+/// Returns a heuristic evaluation of a specified position on a board with the
+/// specified geometry for the current player, which is positive if the current
+/// player is ahead.
+///
+/// If the game is over the score is `WIN_SCORE`, zero, or `-WIN_SCORE`
+/// according to the victory status, plus the weighted margin. Otherwise the
+/// evaluation combines the margin, the tiles that each player has in lines
+/// that contain none of the opposing player's tiles (so could still be
+/// completed), and the difference in the sizes of the players' hands.
+pub fn evaluate(game: &WideGame, geometry: &WideGeometry) -> i32 {
+    Searcher::new(geometry, Limits::default()).evaluate(game)
+}
+
+/// Returns the best move and its score from a specified position on a board
+/// with the specified geometry, searching the specified number of moves ahead
+/// with alpha-beta pruning and evaluating the positions reached heuristically.
+///
+/// Passes do not count towards the depth.
+///
+/// # Panics
+///
+/// Panics if the depth is zero.
+pub fn search_to_depth(game: WideGame, geometry: &WideGeometry, depth: u32) -> SearchResult {
+    assert!(depth > 0, "the search must be at least one move deep");
+
+    Searcher::new(geometry, Limits::default())
+        .search_root(&game, depth, None)
+        .expect("the search has no limits")
+}
+
+/// Returns the best move and its score from a specified position on a board
+/// with the specified geometry, searching one move deeper at a time until any
+/// of the specified limits is reached or the end of the game is reached on
+/// every line searched.
+///
+/// The result of the deepest search that finished is returned, and the search
+/// to a depth of one always finishes. The game budget of the limits applies to
+/// the number of positions visited after that search.
+pub fn search(game: WideGame, geometry: &WideGeometry, limits: &Limits) -> SearchResult {
+    let mut searcher = Searcher::new(geometry, Limits::default());
+    let mut result = searcher
+        .search_root(&game, 1, None)
+        .expect("the search has no limits");
+
+    searcher.limiter = Limiter::new(*limits, searcher.nodes);
+
+    while !result.exact {
+        // The best move from the last search is searched first, as it is
+        // likely to remain best and so prunes the most.
+        match searcher.search_root(&game, result.depth + 1, result.best_move) {
+            Some(next) => result = next,
+            None => break,
+        }
+    }
+
+    SearchResult {
+        nodes: searcher.nodes,
+        ..result
+    }
+}
+
+/// Holds the state of a heuristic search.
+struct Searcher<'a> {
+    /// The geometry of the board.
+    geometry: &'a WideGeometry,
+
+    /// The lines of squares that score.
+    lines: Vec<u64>,
+
+    /// The limiter checked at each position visited.
+    limiter: Limiter<'a>,
+
+    /// The number of positions visited.
+    nodes: u64,
+
+    /// Whether every line of play searched so far in the current iteration has
+    /// reached the end of the game.
+    exact: bool,
+}
+
+impl<'a> Searcher<'a> {
+    /// Returns a searcher for the specified geometry with the specified limits.
+    fn new(geometry: &'a WideGeometry, limits: Limits<'a>) -> Self {
+        Searcher {
+            geometry,
+            lines: geometry.get_lines().into_iter().map(u64::from).collect(),
+            limiter: Limiter::new(limits, 0),
+            nodes: 0,
+            exact: true,
+        }
+    }
+
+    /// Returns the result of searching to the specified depth, searching the
+    /// specified move first if there is one, or `None` if a limit is reached.
+    fn search_root(
+        &mut self,
+        game: &WideGame,
+        depth: u32,
+        first: Option<Move>,
+    ) -> Option<SearchResult> {
+        self.exact = true;
+
+        let mut moves = self.get_moves(game);
+
+        if let Some(index) = moves
+            .iter()
+            .position(|&player_move| Some(player_move) == first)
+        {
+            moves[..=index].rotate_right(1);
+        }
+
+        let mut best_move = None;
+        let mut alpha = -INFINITY;
+
+        for player_move in moves {
+            // Passes do not count towards the depth.
+            let child_depth = if player_move == Move::Pass {
+                depth
+            } else {
+                depth - 1
+            };
+
+            let score = -self.negamax(
+                &self.after(game, player_move),
+                child_depth,
+                -INFINITY,
+                -alpha,
+            )?;
+
+            if best_move.is_none() || score > alpha {
+                best_move = Some(player_move);
+                alpha = score;
+            }
+        }
+
+        Some(SearchResult {
+            best_move,
+            score: if best_move.is_some() {
+                alpha
+            } else {
+                self.evaluate(game)
+            },
+            depth,
+            nodes: self.nodes,
+            exact: self.exact,
+        })
+    }
+
+    /// Recursively calculates the score for the current player using negamax
+    /// with alpha-beta pruning, searching the specified number of moves ahead,
+    /// or returns `None` if a limit is reached.
+    ///
+    /// If the score lies within the window from `alpha` to `beta` it is
+    /// returned. Otherwise a value outside the window that bounds the score is
+    /// returned.
+    fn negamax(&mut self, game: &WideGame, depth: u32, mut alpha: i32, beta: i32) -> Option<i32> {
+        if self.limiter.should_stop(self.nodes) {
+            return None;
+        }
+
+        self.nodes += 1;
+
+        if self.geometry.is_over(game) {
+            return Some(self.evaluate(game));
+        }
+
+        if game.player_must_pass() {
+            return self
+                .negamax(&self.geometry.with_pass(game), depth, -beta, -alpha)
+                .map(|score| -score);
+        }
+
+        if depth == 0 {
+            self.exact = false;
+            return Some(self.evaluate(game));
+        }
+
+        // Assume the worst possible score until we have found a better result.
+        let mut best = -INFINITY;
+
+        for player_move in self.get_moves(game) {
+            let score = -self.negamax(&self.after(game, player_move), depth - 1, -beta, -alpha)?;
+
+            if score > best {
+                best = score;
+
+                if score > alpha {
+                    alpha = score;
+
+                    // The opposing player will avoid this position, so we can
+                    // return early.
+                    if alpha >= beta {
+                        return Some(best);
+                    }
+                }
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Returns the evaluation of the specified position described for
+    /// `evaluate`.
+    fn evaluate(&self, game: &WideGame) -> i32 {
+        let margin = i32::from(self.geometry.get_margin(game));
+
+        if self.geometry.is_over(game) {
+            return margin.signum() * WIN_SCORE + margin * MARGIN_WEIGHT;
+        }
+
+        let board = u64::from(game.get_board());
+        let opponent_board = u64::from(game.get_opponent_board());
+
+        let lines: i32 = self
+            .lines
+            .iter()
+            .map(|&line| {
+                match (
+                    (line & board).count_ones(),
+                    (line & opponent_board).count_ones(),
+                ) {
+                    (tiles, 0) if tiles < line.count_ones() => tiles as i32,
+                    (0, tiles) if tiles < line.count_ones() => -(tiles as i32),
+                    _ => 0,
+                }
+            })
+            .sum();
+
+        let tiles = game.get_hand().len() as i32 - game.get_opponent_hand().len() as i32;
+
+        margin * MARGIN_WEIGHT + lines * LINE_WEIGHT + tiles * TILE_WEIGHT
+    }
+
+    /// Returns the moves available in the specified position, or a pass if the
+    /// player must pass.
+    fn get_moves(&self, game: &WideGame) -> Vec<Move> {
+        if self.geometry.is_over(game) {
+            return Vec::new();
+        }
+
+        if game.player_must_pass() {
+            return vec![Move::Pass];
+        }
+
+        let mut moves = Vec::new();
+        let mut available = !u64::from(game.get_unavailable());
+
+        while available != 0 {
+            let square = available.trailing_zeros() as u8;
+            available &= available - 1;
+
+            for tile in TILES_PREFERENCE {
+                if game.has(tile) {
+                    moves.push(Move::Play(tile, square));
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Returns the position after the specified move.
+    fn after(&self, game: &WideGame, player_move: Move) -> WideGame {
+        match player_move {
+            Move::Play(tile, square) => self.geometry.with_move(game, tile, square),
+            Move::Pass => self.geometry.with_pass(game),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use crate::solver;
+    use crate::status::Status::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn test_evaluate() {
+        let geometry = WideGeometry::rectangle(5, 5, 3);
+        let game = geometry.initial_game();
+        assert_eq!(0, evaluate(&game, &geometry));
+
+        // After a move, the opposing player has a tile in the lines through
+        // the centre square and a smaller hand.
+        let game = geometry.with_move(&game, Diagonal, 18);
+        assert_eq!(-12 * LINE_WEIGHT + TILE_WEIGHT, evaluate(&game, &geometry));
+
+        // The game is over once the current player's hand is empty.
+        let game = game.with_hands(Hand::new([0, 0, 0, 0]), Hand::new([0, 0, 0, 1]));
+        assert_eq!(0, evaluate(&game, &geometry));
+
+        // The margin on a full 8×8 board with lines of two squares is more than
+        // fits in a byte.
+        let geometry = WideGeometry::rectangle(8, 8, 2);
+        let game = WideGame::new(
+            geometry.get_playable(),
+            Hand::default(),
+            Default::default(),
+            Hand::default(),
+            geometry.get_playable(),
+        );
+        assert_eq!(WIN_SCORE + 210 * MARGIN_WEIGHT, evaluate(&game, &geometry));
+    }
+
+    #[test]
+    fn test_search_to_depth() {
+        // The search reaches the end of the game on a small board, so the
+        // score agrees with the solver.
+        let geometry = WideGeometry::rectangle(3, 3, 3);
+        let game = geometry
+            .initial_game()
+            .with_hands(Hand::new([1, 1, 0, 1]), Hand::new([0, 1, 1, 1]));
+
+        let result = search_to_depth(game, &geometry, 6);
+        assert!(result.exact);
+        assert_eq!(
+            solver::solve_wide(game, &geometry, &mut 0),
+            match result.score {
+                score if score >= WIN_SCORE => Win,
+                score if score <= -WIN_SCORE => Loss,
+                _ => Draw,
+            }
+        );
+
+        let result = search_to_depth(game, &geometry, 2);
+        assert!(!result.exact);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_search() {
+        let geometry = WideGeometry::rectangle(6, 6, 4);
+        let game = geometry.initial_game();
+
+        let result = search(game, &geometry, &Limits::default().with_max_games(100_000));
+        assert!(result.depth >= 2);
+        assert!(!result.exact);
+
+        let Some(Move::Play(tile, square)) = result.best_move else {
+            panic!("the search should find a move");
+        };
+        assert!(game.is_available(square) && game.has(tile));
+
+        // The search stops early once the end of the game is reached.
+        let game = game.with_hands(Hand::new([0, 1, 0, 0]), Hand::new([0, 0, 1, 0]));
+        let result = search(game, &geometry, &Limits::default());
+        assert!(result.exact);
+        assert_eq!(2, result.depth);
+    }
+}
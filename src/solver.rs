@@ -1,8 +1,17 @@
-//! Provides a function for solving Mijnlieff.
+//! Provides functions for solving Mijnlieff.
+
+use std::time::Duration;
 
 use crate::game::Game;
+use crate::geometry::BoardGeometry;
+use crate::limits::{Limiter, Limits, SolveOutcome};
+use crate::moves::Move;
+use crate::progress::{Progress, Reporter};
 use crate::status::Status::{self, *};
+use crate::table::TranspositionTable;
+use crate::tablebase::Tablebase;
 use crate::tile::Tile::{self, *};
+use crate::wide::{WideGame, WideGeometry};
 
 /// The order in which squares are analysed.
 ///
@@ -17,7 +26,8 @@ use crate::tile::Tile::{self, *};
 /// central square. So corner squares are tried next.
 ///
 /// All other squares are then tried in order.
-const SQUARES_PREFERENCE: [u8; 16] = [5, 6, 9, 10, 3, 15, 12, 0, 1, 2, 4, 7, 8, 11, 13, 14];
+pub(crate) const SQUARES_PREFERENCE: [u8; 16] =
+    [5, 6, 9, 10, 3, 15, 12, 0, 1, 2, 4, 7, 8, 11, 13, 14];
 
 /// The order in which tiles are analysed.
 ///
@@ -32,19 +42,219 @@ const SQUARES_PREFERENCE: [u8; 16] = [5, 6, 9, 10, 3, 15, 12, 0, 1, 2, 4, 7, 8,
 ///
 /// Diagonals are tried third and Pullers ar tried last as Pullers are less
 /// useful in maintaining control of the central squares early in the game.
-const TILES_PREFERENCE: [Tile; 4] = [Pusher, Straight, Diagonal, Puller];
+pub(crate) const TILES_PREFERENCE: [Tile; 4] = [Pusher, Straight, Diagonal, Puller];
 
 /// Recursively solves Mijnlieff from a specified game position.
 ///
 /// The second parameter is updated with a count of the number games analysed.
 pub fn solve(game: Game, games: &mut u64) -> Status {
+    solve_hooked(game, games, &mut ()).expect("the solve has no limits")
+}
+
+/// Observes and steers a solve by `solve_hooked`.
+///
+/// Every method does nothing by default, and `solve_hooked` is monomorphised
+/// for each hook, so the unit hook used by `solve` costs nothing.
+pub(crate) trait SolveHook {
+    /// Returns whether the solve should stop before analysing another game,
+    /// given the count of games analysed, which is called for every game.
+    #[inline(always)]
+    fn should_stop(&mut self, _games: u64) -> bool {
+        false
+    }
+
+    /// Returns the victory status of the specified position, which is not over,
+    /// if it can be found without solving its moves.
+    ///
+    /// The second parameter is updated with a count of any games analysed.
+    #[inline(always)]
+    fn lookup(&mut self, _game: &Game, _games: &mut u64) -> Option<Status> {
+        None
+    }
+
+    /// Records the victory status of a position whose moves have been solved,
+    /// and the first move (in order of preference) achieving it.
+    #[inline(always)]
+    fn record(&mut self, _game: &Game, _status: Status, _player_move: Move) {}
+
+    /// Records that the specified move from the position being solved is
+    /// about to be solved by `solve_moves`.
+    #[inline(always)]
+    fn start_move(&mut self, _player_move: Move) {}
+
+    /// Records that the move from the position being solved passed to
+    /// `start_move` has been solved.
+    #[inline(always)]
+    fn finish_move(&mut self) {}
+}
+
+impl SolveHook for () {}
+
+/// Recursively solves Mijnlieff from a specified game position in the same way
+/// as `solve`, calling the specified hook, and returning `None` if the hook
+/// stops the solve.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub(crate) fn solve_hooked<H: SolveHook>(
+    game: Game,
+    games: &mut u64,
+    hook: &mut H,
+) -> Option<Status> {
     if game.is_over() {
+        // The hook is called before the game is analysed, so a game that would
+        // exceed a budget is not counted.
+        if hook.should_stop(*games) {
+            return None;
+        }
+
         *games += 1;
-        return game.get_status();
+        return Some(game.get_status());
+    }
+
+    if let Some(status) = hook.lookup(&game, games) {
+        return Some(status);
     }
 
     if game.player_must_pass() {
-        return !solve(game.with_pass(), games);
+        let status = !solve_hooked(game.with_pass(), games, hook)?;
+        hook.record(&game, status, Move::Pass);
+        return Some(status);
+    }
+
+    // Assume a loss until we have found a better result, and that the first
+    // move achieves it.
+    let mut status = Loss;
+    let mut best_move = None;
+
+    for square in SQUARES_PREFERENCE {
+        if game.is_available(square) {
+            for tile in TILES_PREFERENCE {
+                if game.has(tile) {
+                    let player_move = Move::Play(tile, square);
+
+                    match solve_hooked(game.with_move(tile, square), games, hook)? {
+                        Win => {
+                            best_move.get_or_insert(player_move);
+                        }
+                        Draw => {
+                            if status == Loss {
+                                status = Draw;
+                                best_move = Some(player_move);
+                            }
+                        }
+                        // A win can't be improved upon, so we can return early.
+                        Loss => {
+                            hook.record(&game, Win, player_move);
+                            return Some(Win);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // A game that is not over has a move, and the move is not tracked at all
+    // for hooks that do not record it.
+    if let Some(player_move) = best_move {
+        hook.record(&game, status, player_move);
+    }
+
+    Some(status)
+}
+
+/// Solves each move from a specified game position with `solve_hooked`, in
+/// order of preference, telling the hook which move is being solved.
+///
+/// If the solve finishes, the status is the same as that returned by `solve`.
+/// Otherwise the outcome contains the status of each move from the position
+/// that was solved before the hook stopped the solve.
+///
+/// The second parameter is updated with a count of the number games analysed.
+fn solve_moves<H: SolveHook>(game: Game, games: &mut u64, hook: &mut H) -> SolveOutcome {
+    let moves = get_moves(&game);
+
+    if moves.is_empty() {
+        return solve_hooked(game, games, hook)
+            .map_or_else(|| SolveOutcome::Stopped(Vec::new()), SolveOutcome::Solved);
+    }
+
+    let mut proven = Vec::new();
+
+    for player_move in moves {
+        hook.start_move(player_move);
+
+        let Some(move_status) = solve_hooked(game.after(player_move), games, hook) else {
+            return SolveOutcome::Stopped(proven);
+        };
+
+        hook.finish_move();
+
+        // A win can't be improved upon, so we can return early.
+        if move_status == Loss {
+            return SolveOutcome::Solved(Win);
+        }
+
+        proven.push((player_move, !move_status));
+    }
+
+    SolveOutcome::Solved(if proven.iter().any(|&(_, status)| status == Draw) {
+        Draw
+    } else {
+        Loss
+    })
+}
+
+/// Solves Mijnlieff from a specified game position in the same way as `solve`,
+/// calling the specified function with the progress of the solve at most once
+/// per interval.
+///
+/// The time is only checked every 65,536 games, so the function may be
+/// called slightly less often than the interval suggests, and is not called at
+/// all for quick solves.
+///
+/// The second parameter is updated with the same count of the number games
+/// analysed as `solve`.
+pub fn solve_with_progress(
+    game: Game,
+    games: &mut u64,
+    interval: Duration,
+    report: impl FnMut(&Progress),
+) -> Status {
+    let mut reporter = Reporter::new(get_moves(&game).len(), *games, interval, report);
+
+    solve_moves(game, games, &mut reporter)
+        .status()
+        .expect("the solve has no limits")
+}
+
+/// Solves Mijnlieff from a specified game position in the same way as `solve`,
+/// stopping once any of the specified limits is reached.
+///
+/// If the solve finishes, the status is the same as that returned by `solve`.
+/// Otherwise the outcome contains the status of each move from the position
+/// that was solved before stopping.
+///
+/// The third parameter is updated with a count of the number games analysed,
+/// which is the same as for `solve` if the solve finishes.
+pub fn solve_with_limits(game: Game, limits: &Limits, games: &mut u64) -> SolveOutcome {
+    solve_moves(game, games, &mut Limiter::new(*limits, *games))
+}
+
+/// Recursively solves Mijnlieff from a specified game position on a board with
+/// the specified geometry.
+///
+/// This is slower than `solve`, which uses tables fixed at compile time for
+/// the standard board, but gives the same status on the standard board.
+///
+/// The third parameter is updated with a count of the number games analysed.
+pub fn solve_on(game: Game, geometry: &BoardGeometry, games: &mut u64) -> Status {
+    if geometry.is_over(&game) {
+        *games += 1;
+        return geometry.get_status(&game);
+    }
+
+    if game.player_must_pass() {
+        return !solve_on(geometry.with_pass(&game), geometry, games);
     }
 
     // Assume a loss until we have found a better result.
@@ -54,7 +264,7 @@ pub fn solve(game: Game, games: &mut u64) -> Status {
         if game.is_available(square) {
             for tile in TILES_PREFERENCE {
                 if game.has(tile) {
-                    match solve(game.with_move(tile, square), games) {
+                    match solve_on(geometry.with_move(&game, tile, square), geometry, games) {
                         Win => (),
                         Draw => status = Draw,
                         // A win can't be improved upon, so we can return early.
@@ -68,10 +278,296 @@ pub fn solve(game: Game, games: &mut u64) -> Status {
     status
 }
 
+/// Recursively solves Mijnlieff from a specified game position on a board of
+/// up to 64 squares with the specified geometry.
+///
+/// Squares are tried in numerical order, as `SQUARES_PREFERENCE` only applies
+/// to boards of 16 squares.
+///
+/// The third parameter is updated with a count of the number games analysed.
+pub fn solve_wide(game: WideGame, geometry: &WideGeometry, games: &mut u64) -> Status {
+    if geometry.is_over(&game) {
+        *games += 1;
+        return geometry.get_status(&game);
+    }
+
+    if game.player_must_pass() {
+        return !solve_wide(geometry.with_pass(&game), geometry, games);
+    }
+
+    // Assume a loss until we have found a better result.
+    let mut status = Loss;
+    let mut available = !u64::from(game.get_unavailable());
+
+    while available != 0 {
+        let square = available.trailing_zeros() as u8;
+        available &= available - 1;
+
+        for tile in TILES_PREFERENCE {
+            if game.has(tile) {
+                match solve_wide(geometry.with_move(&game, tile, square), geometry, games) {
+                    Win => (),
+                    Draw => status = Draw,
+                    // A win can't be improved upon, so we can return early.
+                    Loss => return Win,
+                }
+            }
+        }
+    }
+
+    status
+}
+
+/// The minimum number of tiles remaining in both players' hands for a position
+/// to be cached in a transposition table.
+///
+/// Caching positions closer to the end of the game fills the table with
+/// positions that are rarely reached again, and the cost of looking them up
+/// outweighs the cost of solving them.
+const TABLE_MIN_TILES_REMAINING: u8 = 7;
+
+/// Recursively solves Mijnlieff from a specified game position, using a
+/// transposition table to avoid solving positions more than once.
+///
+/// The victory status of positions with moves to analyse is cached in the
+/// table under their canonical instance, so that symmetric positions share an
+/// entry, except near the end of the game. The status is the same as that
+/// returned by `solve`, but fewer games are analysed.
+///
+/// The third parameter is updated with a count of the number games analysed.
+pub fn solve_with_table(game: Game, table: &mut TranspositionTable, games: &mut u64) -> Status {
+    solve_hooked(game, games, table).expect("the solve has no limits")
+}
+
+impl SolveHook for TranspositionTable {
+    fn lookup(&mut self, game: &Game, games: &mut u64) -> Option<Status> {
+        // Near the end of the game, solving a position is quicker than looking
+        // it up in the table.
+        if game.tiles_remaining() < TABLE_MIN_TILES_REMAINING {
+            return Some(solve(*game, games));
+        }
+
+        // Symmetric positions have the same status, so share an entry.
+        self.get(&game.canonical())
+    }
+
+    fn record(&mut self, game: &Game, status: Status, _player_move: Move) {
+        self.insert(game.canonical(), status);
+    }
+}
+
+/// Recursively solves Mijnlieff from a specified game position, probing the
+/// specified tablebase before recursing into positions with few enough tiles
+/// remaining.
+///
+/// Positions not stored in the tablebase, such as those not reachable from the
+/// position from which it was generated, are solved as in `solve`.
+///
+/// The third parameter is updated with a count of the number games analysed,
+/// which does not include games in positions found in the tablebase.
+pub fn solve_with_tablebase(game: Game, tablebase: &Tablebase, games: &mut u64) -> Status {
+    solve_hooked(game, games, &mut &*tablebase).expect("the solve has no limits")
+}
+
+impl SolveHook for &Tablebase {
+    fn lookup(&mut self, game: &Game, _games: &mut u64) -> Option<Status> {
+        self.probe(game)
+    }
+}
+
+/// Returns the victory status and a line of perfect play from a specified game
+/// position until the end of the game.
+///
+/// At each position, the first move (in order of preference) that achieves the
+/// best possible status is chosen. When every move loses, the first available
+/// move is chosen without further analysis.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub fn principal_variation(game: Game, games: &mut u64) -> (Status, Vec<Move>) {
+    let status = solve(game, games);
+
+    let mut variation = Vec::new();
+    let mut game = game;
+    let mut expected = status;
+
+    while let Some(player_move) = find_move(&game, expected, games) {
+        variation.push(player_move);
+        game = game.after(player_move);
+        expected = !expected;
+    }
+
+    (status, variation)
+}
+
+/// Returns a move achieving the best possible status from a specified game
+/// position, or `None` if the game is over.
+///
+/// The first move (in order of preference) that achieves the best status is
+/// returned, and moves are analysed in the same order as `solve`, so this takes
+/// no longer than solving the position.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub fn best_move(game: Game, games: &mut u64) -> Option<Move> {
+    let mut best: Option<(Move, Status)> = None;
+
+    for player_move in get_moves(&game) {
+        let status = !solve(game.after(player_move), games);
+
+        match (status, best) {
+            // A win can't be improved upon, so we can return early.
+            (Win, _) => return Some(player_move),
+            (Draw, Some((_, Loss)) | None) | (Loss, None) => best = Some((player_move, status)),
+            _ => (),
+        }
+    }
+
+    best.map(|(player_move, _)| player_move)
+}
+
+/// Returns each move available from a specified game position, paired with
+/// every reply that wins for the opposing player.
+///
+/// Moves for which the opposing player has no winning reply are paired with an
+/// empty list.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub fn winning_replies(game: Game, games: &mut u64) -> Vec<(Move, Vec<Move>)> {
+    get_moves(&game)
+        .into_iter()
+        .map(|player_move| {
+            let reply_game = game.after(player_move);
+
+            let replies = get_moves(&reply_game)
+                .into_iter()
+                .filter(|&reply| solve(reply_game.after(reply), games) == Loss)
+                .collect();
+
+            (player_move, replies)
+        })
+        .collect()
+}
+
+/// Returns the first move (in order of preference) from a specified game
+/// position that leads to the specified status for the current player, or
+/// `None` if the game is over.
+fn find_move(game: &Game, status: Status, games: &mut u64) -> Option<Move> {
+    let moves = get_moves(game);
+
+    // Every move leads to a loss, so there is no need to analyse them.
+    if status == Loss {
+        return moves.first().copied();
+    }
+
+    moves
+        .into_iter()
+        .find(|&player_move| !solve(game.after(player_move), games) == status)
+}
+
+/// Returns the moves available from a specified game position, in order of
+/// preference.
+pub(crate) fn get_moves(game: &Game) -> Vec<Move> {
+    if game.is_over() {
+        return Vec::new();
+    }
+
+    if game.player_must_pass() {
+        return vec![Move::Pass];
+    }
+
+    let mut moves = Vec::new();
+
+    for square in SQUARES_PREFERENCE {
+        if game.is_available(square) {
+            for tile in TILES_PREFERENCE {
+                if game.has(tile) {
+                    moves.push(Move::Play(tile, square));
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+/// Recursively calculates the final margin by which the current player wins
+/// (or, if negative, loses) from a specified game position, with both players
+/// playing to maximise their own margin.
+///
+/// The second parameter is updated with a count of the number games analysed.
+pub fn solve_margin(game: Game, games: &mut u64) -> i8 {
+    negamax(game, -MAX_MARGIN, MAX_MARGIN, games)
+}
+
+/// Returns whether the current player can guarantee finishing at least the
+/// specified margin ahead of the opposing player.
+///
+/// This searches with a null window, so is much quicker than calculating the
+/// exact margin with `solve_margin`. For example, calling this with a margin of
+/// 2 on a position known to be a win determines whether the win can be by more
+/// than a single line.
+///
+/// The third parameter is updated with a count of the number games analysed.
+pub fn solve_margin_at_least(game: Game, margin: i8, games: &mut u64) -> bool {
+    negamax(game, margin - 1, margin, games) >= margin
+}
+
+/// An upper bound on the absolute value of any margin, which is used as the
+/// initial search window.
+const MAX_MARGIN: i8 = 24;
+
+/// Recursively calculates the margin for the current player using negamax
+/// with alpha-beta pruning.
+///
+/// If the exact margin lies within the window from `alpha` to `beta` it is
+/// returned. Otherwise a value outside the window that bounds the exact margin
+/// is returned.
+fn negamax(game: Game, mut alpha: i8, beta: i8, games: &mut u64) -> i8 {
+    if game.is_over() {
+        *games += 1;
+        return game.get_margin();
+    }
+
+    if game.player_must_pass() {
+        return -negamax(game.with_pass(), -beta, -alpha, games);
+    }
+
+    // Assume the worst possible margin until we have found a better result.
+    let mut best = -MAX_MARGIN;
+
+    for square in SQUARES_PREFERENCE {
+        if game.is_available(square) {
+            for tile in TILES_PREFERENCE {
+                if game.has(tile) {
+                    let margin = -negamax(game.with_move(tile, square), -beta, -alpha, games);
+
+                    if margin > best {
+                        best = margin;
+
+                        if margin > alpha {
+                            alpha = margin;
+
+                            // The opposing player will avoid this position, so
+                            // we can return early.
+                            if alpha >= beta {
+                                return best;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    best
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tile::Tile;
+    use crate::board::Board;
+    use crate::fixtures::create_game;
+    use crate::moves::Move::*;
+    use std::sync::atomic::AtomicBool;
 
     #[test]
     fn test_solve() {
@@ -142,32 +638,303 @@ mod tests {
         );
     }
 
-    // Creates a the following board arrangement, with every unoccupied square
-    // available for the next move:
-    //
-    // 1 2 1 2
-    // 2 1 2 1
-    // 1 2 . .
-    // 1 2 . .
-    //
-    // The specified tiles are used, so the solver can be tested with the
-    // remaining tiles. Note that this function takes advantage of the fact that
-    // the validity of moves isn't checked, and it makes mostly invalid moves.
-    fn create_game(tile_1: Tile, tile_2: Tile, tile_3: Tile) -> Game {
-        Game::default()
-            .with_move(tile_1, 0)
-            .with_move(tile_1, 1)
-            .with_move(tile_1, 2)
-            .with_move(tile_1, 3)
-            .with_move(tile_2, 7)
-            .with_move(tile_2, 6)
-            .with_move(tile_2, 5)
-            .with_move(tile_2, 4)
-            .with_move(tile_3, 8)
-            .with_move(tile_3, 9)
-            .with_move(tile_3, 12)
-            .with_move(tile_3, 13)
-            .with_pass()
-            .with_pass()
+    #[test]
+    fn test_solve_with_progress() {
+        let game = Game::default()
+            .with_move(Puller, 0)
+            .with_move(Pusher, 5)
+            .with_move(Straight, 3)
+            .with_move(Straight, 12)
+            .with_move(Diagonal, 10);
+
+        let mut games = 0;
+        let mut progress_games = 0;
+        let mut reports = Vec::new();
+
+        assert_eq!(
+            solve(game, &mut games),
+            solve_with_progress(game, &mut progress_games, Duration::ZERO, |progress| {
+                reports.push(*progress)
+            })
+        );
+        assert_eq!(games, progress_games);
+
+        // The time is checked every 65,536 games, and every check reports
+        // progress with a zero interval.
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|pair| pair[0].games < pair[1].games));
+
+        let moves = get_moves(&game);
+
+        for progress in reports {
+            assert_eq!(moves.len(), progress.root_moves);
+            assert!(progress.games <= games);
+            assert_eq!(
+                Some(moves[progress.root_moves_completed]),
+                progress.current_move
+            );
+        }
+    }
+
+    #[test]
+    fn test_solve_with_limits() {
+        let game = Game::default()
+            .with_move(Puller, 0)
+            .with_move(Pusher, 5)
+            .with_move(Straight, 3)
+            .with_move(Straight, 12)
+            .with_move(Diagonal, 10);
+
+        let mut games = 0;
+        let status = solve(game, &mut games);
+
+        let mut limited_games = 0;
+        assert_eq!(
+            SolveOutcome::Solved(status),
+            solve_with_limits(game, &Limits::default(), &mut limited_games)
+        );
+        assert_eq!(games, limited_games);
+
+        // A budget of exactly the games needed is enough, but one fewer is not.
+        assert_eq!(
+            SolveOutcome::Solved(status),
+            solve_with_limits(game, &Limits::default().with_max_games(games), &mut 0)
+        );
+
+        let mut limited_games = 0;
+        assert!(matches!(
+            solve_with_limits(
+                game,
+                &Limits::default().with_max_games(games - 1),
+                &mut limited_games
+            ),
+            SolveOutcome::Stopped(_)
+        ));
+        assert_eq!(games - 1, limited_games);
+
+        // Stopping partway through leaves some moves proven.
+        let mut limited_games = 0;
+        let SolveOutcome::Stopped(proven) = solve_with_limits(
+            game,
+            &Limits::default().with_max_games(games * 2 / 3),
+            &mut limited_games,
+        ) else {
+            panic!("the solve should stop");
+        };
+        assert_eq!(games * 2 / 3, limited_games);
+        assert!(!proven.is_empty());
+
+        for (player_move, status) in proven {
+            assert_eq!(status, !solve(game.after(player_move), &mut 0));
+        }
+
+        let cancelled = AtomicBool::new(true);
+        assert_eq!(
+            SolveOutcome::Stopped(Vec::new()),
+            solve_with_limits(
+                game,
+                &Limits::default().with_cancel_flag(&cancelled),
+                &mut 0
+            )
+        );
+    }
+
+    #[test]
+    fn test_solve_on() {
+        let geometry = BoardGeometry::default();
+
+        for game in [
+            create_game(Puller, Straight, Diagonal),
+            create_game(Puller, Pusher, Diagonal),
+            create_game(Puller, Pusher, Straight),
+        ] {
+            let mut games = 0;
+            let mut geometry_games = 0;
+
+            assert_eq!(
+                solve(game, &mut games),
+                solve_on(game, &geometry, &mut geometry_games)
+            );
+            assert_eq!(games, geometry_games);
+        }
+
+        // On a 3×3 board, the game ends once all nine squares are occupied,
+        // and the first player can force a win.
+        let geometry = BoardGeometry::new(Board::from(0b_0000_0111_0111_0111), 3);
+        let mut games = 0;
+        assert_eq!(
+            Win,
+            solve_on(geometry.initial_game(), &geometry, &mut games)
+        );
+    }
+
+    #[test]
+    fn test_solve_wide() {
+        // A 3×3 board gives the same status whether it is represented by a
+        // `Board` or a `WideBoard`.
+        let geometry = WideGeometry::rectangle(3, 3, 3);
+        let mut games = 0;
+        assert_eq!(
+            Win,
+            solve_wide(geometry.initial_game(), &geometry, &mut games)
+        );
+
+        // The squares are tried in a different order, so the number of games
+        // analysed differs.
+        let geometry = WideGeometry::rectangle(1, 4, 2);
+        let mut games = 0;
+        let mut board_games = 0;
+        let board_geometry = BoardGeometry::new(Board::from(0b_0000_0000_0000_1111), 2);
+        assert_eq!(
+            solve_on(
+                board_geometry.initial_game(),
+                &board_geometry,
+                &mut board_games
+            ),
+            solve_wide(geometry.initial_game(), &geometry, &mut games)
+        );
+    }
+
+    #[test]
+    fn test_solve_with_tablebase() {
+        // The tablebase covers the last three moves of each position.
+        for game in [
+            create_game(Puller, Straight, Diagonal),
+            create_game(Puller, Pusher, Diagonal),
+            create_game(Puller, Pusher, Straight),
+        ] {
+            let tablebase = Tablebase::generate(game, 3, 2);
+            let mut games = 0;
+            let mut tablebase_games = 0;
+
+            assert_eq!(
+                solve(game, &mut games),
+                solve_with_tablebase(game, &tablebase, &mut tablebase_games)
+            );
+            assert!(tablebase_games < games);
+        }
+    }
+
+    #[test]
+    fn test_solve_margin() {
+        let mut games = 0;
+
+        // See the lines of play documented in `test_solve`, although the
+        // player can win the first position by two lines rather than the one
+        // line shown there
+        assert_eq!(
+            2,
+            solve_margin(create_game(Puller, Straight, Diagonal), &mut games)
+        );
+        assert_eq!(
+            0,
+            solve_margin(create_game(Puller, Pusher, Diagonal), &mut games)
+        );
+        assert_eq!(
+            -1,
+            solve_margin(create_game(Puller, Pusher, Straight), &mut games)
+        );
+    }
+
+    #[test]
+    fn test_solve_margin_at_least() {
+        let mut games = 0;
+
+        let game = create_game(Puller, Straight, Diagonal);
+        assert!(solve_margin_at_least(game, 2, &mut games));
+        assert!(!solve_margin_at_least(game, 3, &mut games));
+
+        let game = create_game(Puller, Pusher, Straight);
+        assert!(solve_margin_at_least(game, -1, &mut games));
+        assert!(!solve_margin_at_least(game, 0, &mut games));
+    }
+
+    #[test]
+    fn test_principal_variation() {
+        let mut games = 0;
+        // See the lines of play documented in `test_solve`
+        assert_eq!(
+            (Win, vec![Play(Pusher, 10), Pass, Play(Pusher, 15), Pass]),
+            principal_variation(create_game(Puller, Straight, Diagonal), &mut games)
+        );
+
+        assert_eq!(
+            (
+                Loss,
+                vec![
+                    Play(Diagonal, 10),
+                    Play(Diagonal, 15),
+                    Pass,
+                    Play(Diagonal, 14),
+                    Play(Diagonal, 11)
+                ]
+            ),
+            principal_variation(create_game(Puller, Pusher, Straight), &mut games)
+        );
+    }
+
+    #[test]
+    fn test_best_move() {
+        let mut games = 0;
+
+        assert_eq!(
+            Some(Play(Pusher, 10)),
+            best_move(create_game(Puller, Straight, Diagonal), &mut games)
+        );
+
+        assert_eq!(
+            None,
+            best_move(
+                create_game(Puller, Straight, Diagonal)
+                    .with_move(Pusher, 10)
+                    .with_pass()
+                    .with_move(Pusher, 15)
+                    .with_pass(),
+                &mut games
+            )
+        );
+    }
+
+    #[test]
+    fn test_winning_replies() {
+        let mut games = 0;
+
+        // See the lines of play documented in `test_solve`
+        assert_eq!(
+            vec![
+                (Play(Diagonal, 10), vec![Play(Diagonal, 15)]),
+                (Play(Diagonal, 15), vec![Play(Diagonal, 10)]),
+                (Play(Diagonal, 11), vec![Play(Diagonal, 14)]),
+                (Play(Diagonal, 14), vec![Play(Diagonal, 11)])
+            ],
+            winning_replies(create_game(Puller, Pusher, Straight), &mut games)
+        );
+
+        assert!(
+            winning_replies(create_game(Puller, Straight, Diagonal), &mut games)
+                .iter()
+                .all(|(_, replies)| replies.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_solve_with_table() {
+        let game = Game::default()
+            .with_move(Puller, 0)
+            .with_move(Pusher, 5)
+            .with_move(Straight, 3)
+            .with_move(Straight, 12)
+            .with_move(Diagonal, 10);
+
+        let mut games = 0;
+        let mut table = TranspositionTable::new(1 << 20);
+        let mut table_games = 0;
+
+        assert_eq!(
+            solve(game, &mut games),
+            solve_with_table(game, &mut table, &mut table_games)
+        );
+        assert!(table_games < games);
+        assert!(table.hits() > 0);
     }
 }
@@ -3,7 +3,7 @@
 use std::ops::Not;
 
 /// Represents the victory status.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Status {
     Win,
     Draw,
@@ -0,0 +1,141 @@
+//! Defines a struct representing a transposition table.
+
+use std::mem;
+
+use crate::game::Game;
+use crate::status::Status;
+
+/// Represents a transposition table, which caches the victory status of game
+/// positions so that positions reached by different orders of moves need only
+/// be solved once.
+///
+/// The table has a fixed number of entries, which is a power of two, and each
+/// game position can only be stored in a single entry determined by hashing
+/// its key. When two positions hash to the same entry, the most recently
+/// stored position replaces the other.
+pub struct TranspositionTable {
+    /// The entries, which are `None` until a position has been stored.
+    entries: Vec<Option<Entry>>,
+
+    /// The number of times a position has been found in the table.
+    hits: u64,
+
+    /// The number of times a position has not been found in the table.
+    misses: u64,
+}
+
+/// Represents an entry in a transposition table.
+#[derive(Copy, Clone)]
+struct Entry {
+    /// The game position.
+    game: Game,
+
+    /// The victory status of the game position.
+    status: Status,
+}
+
+impl TranspositionTable {
+    /// Returns an empty table using at most the specified number of bytes for
+    /// its entries. The table always has at least one entry.
+    pub fn new(bytes: usize) -> Self {
+        let capacity = (bytes / mem::size_of::<Option<Entry>>()).max(1);
+
+        TranspositionTable {
+            // Round down to a power of two so that the index can be masked.
+            entries: vec![None; 1 << capacity.ilog2()],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of entries.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of times a position has been found in the table.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of times a position has not been found in the table.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns the victory status of the specified game position, or `None` if
+    /// the position is not in the table.
+    pub fn get(&mut self, game: &Game) -> Option<Status> {
+        match self.entries[self.index(game)] {
+            Some(entry) if entry.game == *game => {
+                self.hits += 1;
+                Some(entry.status)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Stores the victory status of the specified game position, replacing any
+    /// position previously stored in the same entry.
+    pub fn insert(&mut self, game: Game, status: Status) {
+        let index = self.index(&game);
+        self.entries[index] = Some(Entry { game, status });
+    }
+
+    /// Returns the index of the entry in which the specified game position is
+    /// stored.
+    fn index(&self, game: &Game) -> usize {
+        let key = game.key();
+
+        // Multiplicative hashing mixes the key's bits into the high bits of the
+        // hash, which are folded back into the low bits used for the index.
+        let hash = ((key as u64) ^ ((key >> 64) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15))
+            .wrapping_mul(0xbf58_476d_1ce4_e5b9);
+
+        ((hash ^ (hash >> 32)) as usize) & (self.entries.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TranspositionTable;
+    use crate::game::Game;
+    use crate::status::Status::*;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn new() {
+        assert_eq!(1, TranspositionTable::new(0).capacity());
+        assert_eq!(4, TranspositionTable::new(100).capacity());
+        assert_eq!(65536, TranspositionTable::new(1 << 20).capacity());
+    }
+
+    #[test]
+    fn get() {
+        let mut table = TranspositionTable::new(1 << 10);
+        let game = Game::default().with_move(Pusher, 0);
+
+        assert_eq!(None, table.get(&game));
+        assert_eq!((0, 1), (table.hits(), table.misses()));
+
+        table.insert(game, Win);
+        assert_eq!(Some(Win), table.get(&game));
+        assert_eq!(None, table.get(&game.with_pass()));
+        assert_eq!((1, 2), (table.hits(), table.misses()));
+    }
+
+    #[test]
+    fn insert() {
+        // With a single entry, each position replaces the previous position.
+        let mut table = TranspositionTable::new(0);
+        let game = Game::default().with_move(Pusher, 0);
+
+        table.insert(game, Win);
+        table.insert(game.with_pass(), Draw);
+        assert_eq!(None, table.get(&game));
+        assert_eq!(Some(Draw), table.get(&game.with_pass()));
+    }
+}
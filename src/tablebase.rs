@@ -0,0 +1,840 @@
+//! Defines a struct representing an endgame tablebase.
+
+use std::io::{self, Read, Write};
+use std::thread;
+
+use crate::board::Board;
+use crate::format::{self, FileError, FileKind, Header};
+use crate::game::Game;
+use crate::hand::Hand;
+use crate::solver;
+use crate::status::Status::{self, *};
+use crate::tile::Tile;
+
+/// Describes a tablebase file.
+const KIND: FileKind = FileKind {
+    name: "a tablebase",
+    magic: *b"MLTB",
+    version: 3,
+};
+
+/// The number of statuses packed into each word of the statuses.
+const STATUSES_PER_WORD: u64 = 32;
+
+/// The binomial coefficients, indexed by the size of a set of squares and the
+/// size of a subset.
+const BINOMIALS: [[u64; 17]; 17] = calculate_binomials();
+
+/// Represents an endgame tablebase, which stores the victory status of every
+/// position reachable from a starting position with a specified number of
+/// tiles or fewer remaining in both players' hands.
+///
+/// The tablebase is generated retrogradely. The positions with each number of
+/// tiles remaining (a layer) are enumerated directly rather than by searching
+/// from the starting position, and are solved starting from the layer with no
+/// tiles remaining, so each position is solved by looking up the positions
+/// after each of its moves in the layer below.
+///
+/// Each position is identified by the player to move, each player's hand, the
+/// squares each player has occupied since the starting position, and the last
+/// move: a tile in one of the squares occupied by the player who moved last, a
+/// pass, or none (in which case the unavailable squares are those of the
+/// starting position). Every combination of these with at most the maximum
+/// number of tiles remaining is stored, which includes every reachable
+/// position and some that are not. The positions are ranked densely within
+/// each combination of player to move and hands (a block), with the squares
+/// occupied ranked as combinations of the unoccupied squares of the starting
+/// position, so a position is probed by calculating its index. Each status is
+/// stored in two bits, as described in the `format` module, and each layer
+/// starts at a new word.
+///
+/// A tablebase is written to a file as a 40 byte header, in the format
+/// described in the `format` module, followed by each word of the statuses as
+/// a little-endian `u64`. The header contains the bytes `MLTB`, the maximum
+/// number of tiles remaining, and these fields:
+///
+/// 1. A fingerprint of the rules compiled into `Tile::MOVES` and
+///    `Board::LINES`, so that a tablebase generated under different rules is
+///    rejected.
+/// 2. The low 64 bits of the key of the starting position, as returned by
+///    `Game::key`.
+/// 3. The high 64 bits of the key of the starting position.
+/// 4. A 64 bit FNV-1a checksum of the key of the starting position, as a
+///    little-endian `u128`, followed by the words of the statuses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tablebase {
+    /// The position from which the tablebase was generated.
+    start: Game,
+
+    /// The maximum number of tiles remaining in the positions stored.
+    max_tiles: u8,
+
+    /// The blocks of positions, in order of index.
+    blocks: Vec<Block>,
+
+    /// The index of the first position in each layer, and after the last.
+    layers: Vec<u64>,
+
+    /// The statuses of the positions, packed into words.
+    statuses: Vec<u64>,
+}
+
+/// Represents a block of positions in a tablebase, with the same player to move
+/// and the same hands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Block {
+    /// The key identifying the block, as returned by `Tablebase::block_key`,
+    /// which increases with the index of the block.
+    key: u64,
+
+    /// The index of the first position in the block.
+    offset: u64,
+
+    /// Whether the opponent (the player not to move in the starting position)
+    /// is to move.
+    opponent_to_move: bool,
+
+    /// The hand of the player to move in the starting position.
+    player_hand: Hand,
+
+    /// The hand of the opponent.
+    opponent_hand: Hand,
+
+    /// The number of squares occupied by the player since the starting
+    /// position.
+    player_squares: u8,
+
+    /// The number of squares occupied by the opponent since the starting
+    /// position.
+    opponent_squares: u8,
+
+    /// The number of combinations of squares occupied by the player.
+    player_combinations: u64,
+
+    /// The number of combinations of squares occupied by the opponent for each
+    /// combination of squares occupied by the player.
+    opponent_combinations: u64,
+
+    /// The number of codes for the last move.
+    codes: u64,
+}
+
+impl Block {
+    /// Returns the number of positions in the block.
+    fn len(&self) -> u64 {
+        self.player_combinations * self.opponent_combinations * self.codes
+    }
+}
+
+impl Tablebase {
+    /// Returns the tablebase of every position reachable from the specified
+    /// position with at most the specified number of tiles remaining in both
+    /// players' hands, solving each layer using the specified number of
+    /// threads.
+    ///
+    /// Only positions with at most the maximum number of tiles remaining are
+    /// enumerated, but the number of them grows quickly with the maximum and
+    /// with the number of unoccupied squares in the specified position.
+    pub fn generate(game: Game, max_tiles: u8, threads: usize) -> Self {
+        let mut tablebase = Tablebase::empty(game, max_tiles);
+
+        // The statuses are taken out of the tablebase while they are filled, so
+        // that the threads can share the rest of it.
+        let mut all_statuses = std::mem::take(&mut tablebase.statuses);
+
+        for layer in 0..tablebase.layers.len() - 1 {
+            let (start, end) = (tablebase.layers[layer], tablebase.layers[layer + 1]);
+            let (below, statuses) = all_statuses.split_at_mut((start / STATUSES_PER_WORD) as usize);
+            let statuses = &mut statuses[..end.div_ceil(STATUSES_PER_WORD) as usize - below.len()];
+
+            // Every layer starts at a new word, so the words are divided
+            // between the threads.
+            let chunk_words = statuses.len().div_ceil(threads.max(1)).max(1);
+            let tablebase = &tablebase;
+
+            thread::scope(|scope| {
+                for (chunk, words) in statuses.chunks_mut(chunk_words).enumerate() {
+                    let below = &*below;
+
+                    scope.spawn(move || {
+                        let first = start + (chunk * chunk_words) as u64 * STATUSES_PER_WORD;
+
+                        for (word_index, word) in words.iter_mut().enumerate() {
+                            let word_first = first + word_index as u64 * STATUSES_PER_WORD;
+
+                            for index in word_first..(word_first + STATUSES_PER_WORD).min(end) {
+                                let status =
+                                    tablebase.solve_from(&tablebase.position(index), below);
+                                *word |= u64::from(format::encode_status(status))
+                                    << (index % STATUSES_PER_WORD * 2);
+                            }
+                        }
+                    });
+                }
+            });
+        }
+
+        tablebase.statuses = all_statuses;
+        tablebase
+    }
+
+    /// Returns the tablebase for the specified position and maximum number of
+    /// tiles with every status a loss.
+    fn empty(start: Game, max_tiles: u8) -> Self {
+        let mut blocks = Vec::new();
+        let mut layers = Vec::new();
+        let mut index: u64 = 0;
+
+        let free = !u16::from(Board::merge(start.get_board(), start.get_opponent_board()));
+        let max_tiles = max_tiles.min(start.tiles_remaining());
+
+        for tiles in 0..=max_tiles {
+            index = index.next_multiple_of(STATUSES_PER_WORD);
+            layers.push(index);
+
+            for player_hand in sub_hands(start.get_hand()) {
+                for opponent_hand in sub_hands(start.get_opponent_hand()) {
+                    if player_hand.len() + opponent_hand.len() != tiles {
+                        continue;
+                    }
+
+                    let player_squares = start.get_hand().len() - player_hand.len();
+                    let opponent_squares = start.get_opponent_hand().len() - opponent_hand.len();
+
+                    if u32::from(player_squares + opponent_squares) > free.count_ones() {
+                        continue;
+                    }
+
+                    for opponent_to_move in [false, true] {
+                        // The last move was made by the player not to move.
+                        let last_squares = if opponent_to_move {
+                            u16::from(start.get_board()).count_ones() as u8 + player_squares
+                        } else {
+                            u16::from(start.get_opponent_board()).count_ones() as u8
+                                + opponent_squares
+                        };
+
+                        let block = Block {
+                            key: Tablebase::block_key(
+                                &start,
+                                opponent_to_move,
+                                player_hand,
+                                opponent_hand,
+                            )
+                            .expect("the hands are within the starting hands"),
+                            offset: index,
+                            opponent_to_move,
+                            player_hand,
+                            opponent_hand,
+                            player_squares,
+                            opponent_squares,
+                            player_combinations: BINOMIALS[free.count_ones() as usize]
+                                [usize::from(player_squares)],
+                            opponent_combinations: BINOMIALS
+                                [(free.count_ones() - u32::from(player_squares)) as usize]
+                                [usize::from(opponent_squares)],
+                            codes: u64::from(last_squares) * 4 + 2,
+                        };
+
+                        index += block.len();
+                        blocks.push(block);
+                    }
+                }
+            }
+        }
+
+        layers.push(index);
+
+        Tablebase {
+            start,
+            max_tiles,
+            blocks,
+            statuses: vec![0; index.div_ceil(STATUSES_PER_WORD) as usize],
+            layers,
+        }
+    }
+
+    /// Returns the key identifying the block with the specified player to move
+    /// and hands, or `None` if the hands are not within the starting hands.
+    ///
+    /// The key orders blocks by the number of tiles remaining, then the hands,
+    /// and then the player to move.
+    fn block_key(
+        start: &Game,
+        opponent_to_move: bool,
+        player_hand: Hand,
+        opponent_hand: Hand,
+    ) -> Option<u64> {
+        let (player_index, player_hands) = sub_hand_index(player_hand, start.get_hand())?;
+        let (opponent_index, opponent_hands) =
+            sub_hand_index(opponent_hand, start.get_opponent_hand())?;
+
+        let tiles = u64::from(player_hand.len() + opponent_hand.len());
+
+        Some(
+            ((tiles * player_hands + player_index) * opponent_hands + opponent_index) * 2
+                + u64::from(opponent_to_move),
+        )
+    }
+
+    /// Returns the maximum number of tiles remaining in the positions stored.
+    pub fn max_tiles(&self) -> u8 {
+        self.max_tiles
+    }
+
+    /// Returns the number of positions stored.
+    pub fn len(&self) -> u64 {
+        self.blocks.iter().map(Block::len).sum()
+    }
+
+    /// Returns whether no positions are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the victory status of the specified position, or `None` if the
+    /// position is not stored.
+    pub fn probe(&self, game: &Game) -> Option<Status> {
+        self.index(game)
+            .map(|index| status_at(&self.statuses, index))
+    }
+
+    /// Returns the first position stored whose status differs from the status
+    /// found by `solver::solve`, or `None` if every status is correct.
+    ///
+    /// The second parameter is updated with a count of the number games
+    /// analysed.
+    pub fn verify(&self, games: &mut u64) -> Option<Game> {
+        self.blocks.iter().find_map(|block| {
+            (block.offset..block.offset + block.len()).find_map(|index| {
+                let game = self.position(index);
+                (status_at(&self.statuses, index) != solver::solve(game, games)).then_some(game)
+            })
+        })
+    }
+
+    /// Returns the index of the specified position, or `None` if the position
+    /// is not stored.
+    fn index(&self, game: &Game) -> Option<u64> {
+        if game.tiles_remaining() > self.max_tiles {
+            return None;
+        }
+
+        // If the starting position is symmetric between the players, the
+        // position may be stored with either player to move, and has the same
+        // status under both.
+        [false, true]
+            .into_iter()
+            .find_map(|opponent_to_move| self.index_with(game, opponent_to_move))
+    }
+
+    /// Returns the index of the specified position with the specified player
+    /// to move, or `None` if the position is not stored.
+    fn index_with(&self, game: &Game, opponent_to_move: bool) -> Option<u64> {
+        let (mut boards, mut hands) = (
+            [game.get_board(), game.get_opponent_board()].map(u16::from),
+            [game.get_hand(), game.get_opponent_hand()],
+        );
+
+        if opponent_to_move {
+            boards.swap(0, 1);
+            hands.swap(0, 1);
+        }
+
+        let start_boards = [self.start.get_board(), self.start.get_opponent_board()].map(u16::from);
+        let start_hands = [self.start.get_hand(), self.start.get_opponent_hand()];
+        let free = !(start_boards[0] | start_boards[1]);
+
+        // Each player's squares are those in the starting position and one for
+        // each tile played since.
+        for player in 0..2 {
+            let added = boards[player] & !start_boards[player];
+
+            if boards[player] & start_boards[player] != start_boards[player]
+                || added & !free != 0
+                || added.count_ones() as u8 + hands[player].len() != start_hands[player].len()
+            {
+                return None;
+            }
+        }
+
+        let key = Tablebase::block_key(&self.start, opponent_to_move, hands[0], hands[1])?;
+        let block = &self.blocks[self
+            .blocks
+            .binary_search_by_key(&key, |block| block.key)
+            .ok()?];
+
+        let player_added = boards[0] & !start_boards[0];
+        let opponent_added = boards[1] & !start_boards[1];
+
+        let player_rank = rank(compress(player_added, free));
+        let opponent_rank = rank(compress(opponent_added, free & !player_added));
+        let code = self.code(
+            boards[usize::from(!opponent_to_move)],
+            boards[0] | boards[1],
+            u16::from(game.get_unavailable()),
+        )?;
+
+        Some(
+            block.offset
+                + (player_rank * block.opponent_combinations + opponent_rank) * block.codes
+                + code,
+        )
+    }
+
+    /// Returns the code for a last move resulting in the specified unavailable
+    /// squares, made by the player occupying the first specified board, with
+    /// the second board being every occupied square, or `None` if there is no
+    /// such move.
+    ///
+    /// The code for a tile in the `n`th square occupied by the player is `4n`
+    /// plus the tile discriminant, followed by the codes for a pass and for the
+    /// unavailable squares of the starting position.
+    fn code(&self, last_board: u16, occupied: u16, unavailable: u16) -> Option<u64> {
+        let squares = u64::from(last_board.count_ones());
+
+        if unavailable == occupied {
+            return Some(squares * 4);
+        }
+
+        for (index, square) in set_squares(last_board).enumerate() {
+            for tile in Tile::ALL {
+                if occupied | u16::from(tile.get_unavailable(square)) == unavailable {
+                    return Some(index as u64 * 4 + tile as u64);
+                }
+            }
+        }
+
+        (occupied | u16::from(self.start.get_unavailable()) == unavailable)
+            .then_some(squares * 4 + 1)
+    }
+
+    /// Returns the position with the specified index.
+    fn position(&self, index: u64) -> Game {
+        let block = &self.blocks[self.blocks.partition_point(|block| block.offset <= index) - 1];
+
+        let local = index - block.offset;
+        let code = local % block.codes;
+        let opponent_rank = local / block.codes % block.opponent_combinations;
+        let player_rank = local / block.codes / block.opponent_combinations;
+
+        let start_boards = [self.start.get_board(), self.start.get_opponent_board()].map(u16::from);
+        let free = !(start_boards[0] | start_boards[1]);
+
+        let player_added = expand(unrank(player_rank, block.player_squares), free);
+        let opponent_added = expand(
+            unrank(opponent_rank, block.opponent_squares),
+            free & !player_added,
+        );
+
+        let player_board = start_boards[0] | player_added;
+        let opponent_board = start_boards[1] | opponent_added;
+
+        let last_board = if block.opponent_to_move {
+            player_board
+        } else {
+            opponent_board
+        };
+        let squares = u64::from(last_board.count_ones());
+
+        let unavailable = match code {
+            code if code == squares * 4 => Board::default(),
+            code if code == squares * 4 + 1 => self.start.get_unavailable(),
+            code => {
+                let square = set_squares(last_board)
+                    .nth((code / 4) as usize)
+                    .expect("the code is for a square occupied");
+                Tile::ALL[(code % 4) as usize].get_unavailable(square)
+            }
+        };
+
+        let (player_board, opponent_board) =
+            (Board::from(player_board), Board::from(opponent_board));
+
+        if block.opponent_to_move {
+            Game::new(
+                opponent_board,
+                block.opponent_hand,
+                player_board,
+                block.player_hand,
+                unavailable,
+            )
+        } else {
+            Game::new(
+                player_board,
+                block.player_hand,
+                opponent_board,
+                block.opponent_hand,
+                unavailable,
+            )
+        }
+    }
+
+    /// Returns the victory status of the specified position from the statuses
+    /// of the positions after each of its moves, which are in the layers whose
+    /// statuses are specified.
+    fn solve_from(&self, game: &Game, below: &[u64]) -> Status {
+        if game.is_over() {
+            return game.get_status();
+        }
+
+        // The position after passing is in the same layer, but cannot itself
+        // require a pass, so is solved directly.
+        if game.player_must_pass() {
+            return !self.solve_from(&game.with_pass(), below);
+        }
+
+        let mut best = Loss;
+
+        for player_move in solver::get_moves(game) {
+            let next = game.after(player_move);
+
+            let status = !if next.is_over() {
+                next.get_status()
+            } else {
+                status_at(
+                    below,
+                    self.index(&next)
+                        .expect("every position after a move is stored"),
+                )
+            };
+
+            if format::encode_status(status) > format::encode_status(best) {
+                best = status;
+
+                // A win can't be improved upon, so we can return early.
+                if best == Win {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Writes the tablebase in the format described for `Tablebase`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let key = self.start.key();
+        let header = Header {
+            parameter: self.max_tiles,
+            fields: [
+                format::rules_fingerprint(),
+                key as u64,
+                (key >> 64) as u64,
+                checksum(key, &self.statuses),
+            ],
+        };
+        header.write(&KIND, &mut writer)?;
+
+        for word in &self.statuses {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a tablebase in the format described for `Tablebase`, checking that
+    /// the header is valid and matches the compiled rules, that the starting
+    /// position is valid, that the number of statuses matches the starting
+    /// position, that the key and statuses match the checksum, and that the
+    /// statuses are valid.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, FileError> {
+        let invalid = FileError::InvalidFormat;
+
+        let Header {
+            parameter: max_tiles,
+            fields: [fingerprint, key_low, key_high, expected_checksum],
+        } = Header::read(&KIND, &mut reader)?;
+
+        if fingerprint != format::rules_fingerprint() {
+            return Err(FileError::RulesMismatch);
+        }
+
+        // The key is checked before the tablebase is laid out for it, as an
+        // invalid key could describe hands too large to enumerate.
+        let key = u128::from(key_high) << 64 | u128::from(key_low);
+        let start = Game::try_from_key(key)
+            .ok_or_else(|| invalid(format!("invalid starting position key {key:#x}")))?;
+
+        if max_tiles > start.tiles_remaining() {
+            return Err(invalid(format!(
+                "{max_tiles} tiles exceeds the {} remaining at the start",
+                start.tiles_remaining()
+            )));
+        }
+
+        let mut tablebase = Tablebase::empty(start, max_tiles);
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() != tablebase.statuses.len() * 8 {
+            return Err(invalid(format!(
+                "expected {} bytes of statuses, found {}",
+                tablebase.statuses.len() * 8,
+                bytes.len()
+            )));
+        }
+
+        for (word, bytes) in tablebase.statuses.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().expect("8 bytes"));
+        }
+
+        if checksum(key, &tablebase.statuses) != expected_checksum {
+            return Err(FileError::ChecksumMismatch);
+        }
+
+        // Both bits of a status are only set for an invalid status.
+        if tablebase
+            .statuses
+            .iter()
+            .any(|word| word & word >> 1 & 0x5555_5555_5555_5555 != 0)
+        {
+            return Err(invalid("a status is invalid".to_string()));
+        }
+
+        Ok(tablebase)
+    }
+}
+
+/// Returns the checksum of the specified key of the starting position and
+/// words of the statuses, as written to a file.
+fn checksum(key: u128, statuses: &[u64]) -> u64 {
+    format::fnv1a(
+        key.to_le_bytes()
+            .into_iter()
+            .chain(statuses.iter().flat_map(|word| word.to_le_bytes())),
+    )
+}
+
+/// Returns the status with the specified index in the specified statuses.
+fn status_at(statuses: &[u64], index: u64) -> Status {
+    let word = statuses[(index / STATUSES_PER_WORD) as usize];
+    format::decode_status((word >> (index % STATUSES_PER_WORD * 2) & 0b11) as u8)
+        .expect("statuses are valid")
+}
+
+/// Returns every hand containing at most as many of each tile as the specified
+/// hand.
+fn sub_hands(hand: Hand) -> impl Iterator<Item = Hand> {
+    let counts = Tile::ALL.map(|tile| hand.count(tile));
+
+    (0..=counts[0]).flat_map(move |pullers| {
+        (0..=counts[1]).flat_map(move |pushers| {
+            (0..=counts[2]).flat_map(move |straights| {
+                (0..=counts[3])
+                    .map(move |diagonals| Hand::new([pullers, pushers, straights, diagonals]))
+            })
+        })
+    })
+}
+
+/// Returns the index of the specified hand among the hands returned by
+/// `sub_hands` for the second specified hand, and the number of them, or
+/// `None` if the first hand is not among them.
+fn sub_hand_index(hand: Hand, full: Hand) -> Option<(u64, u64)> {
+    Tile::ALL
+        .into_iter()
+        .try_fold((0, 1), |(index, hands), tile| {
+            let (count, full_count) = (u64::from(hand.count(tile)), u64::from(full.count(tile)));
+            (count <= full_count)
+                .then_some((index * (full_count + 1) + count, hands * (full_count + 1)))
+        })
+}
+
+/// Returns the squares set in the specified bit field, in increasing order.
+fn set_squares(bits: u16) -> impl Iterator<Item = u8> {
+    (0..16).filter(move |&square| bits & 1 << square != 0)
+}
+
+/// Returns the bit field with a bit for each square set in the second bit
+/// field, in order, which is set if the square is set in the first.
+fn compress(bits: u16, within: u16) -> u16 {
+    set_squares(within)
+        .enumerate()
+        .filter(|&(_, square)| bits & 1 << square != 0)
+        .fold(0, |compressed, (index, _)| compressed | 1 << index)
+}
+
+/// Returns the bit field with the squares set in the second bit field for which
+/// the bit in the first bit field is set, in order, as the inverse of
+/// `compress`.
+fn expand(compressed: u16, within: u16) -> u16 {
+    set_squares(within)
+        .enumerate()
+        .filter(|&(index, _)| compressed & 1 << index != 0)
+        .fold(0, |bits, (_, square)| bits | 1 << square)
+}
+
+/// Returns the rank of the specified bit field among bit fields with the same
+/// number of bits set, in colexicographic order.
+fn rank(bits: u16) -> u64 {
+    set_squares(bits)
+        .enumerate()
+        .map(|(index, square)| BINOMIALS[usize::from(square)][index + 1])
+        .sum()
+}
+
+/// Returns the bit field with the specified number of bits set with the
+/// specified rank, as the inverse of `rank`.
+fn unrank(mut rank: u64, bits: u8) -> u16 {
+    let mut result = 0;
+    let mut square = 16;
+
+    for count in (1..=usize::from(bits)).rev() {
+        // Find the highest square whose binomial coefficient fits in the rank.
+        square -= 1;
+
+        while BINOMIALS[square][count] > rank {
+            square -= 1;
+        }
+
+        rank -= BINOMIALS[square][count];
+        result |= 1 << square;
+    }
+
+    result
+}
+
+/// Returns the binomial coefficients up to 16 choose 16.
+///
+/// Only used in evaluating the BINOMIALS constant at compile time.
+const fn calculate_binomials() -> [[u64; 17]; 17] {
+    let mut binomials = [[0; 17]; 17];
+    let mut n = 0;
+
+    while n <= 16 {
+        binomials[n][0] = 1;
+        let mut k = 1;
+
+        while k <= n {
+            binomials[n][k] = binomials[n - 1][k - 1] + binomials[n - 1][k];
+            k += 1;
+        }
+
+        n += 1;
+    }
+
+    binomials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{create_game, create_small_game};
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn rank_and_unrank() {
+        assert_eq!(0, rank(0b0111));
+        assert_eq!(1, rank(0b1011));
+        assert_eq!(BINOMIALS[16][3] - 1, rank(0b_1110_0000_0000_0000));
+
+        for bits in (0..=u16::MAX).step_by(13) {
+            assert_eq!(bits, unrank(rank(bits), bits.count_ones() as u8));
+        }
+
+        assert_eq!(0b0101, compress(0b_1000_0010, 0b_1000_1010));
+        assert_eq!(0b_1000_0010, expand(0b0101, 0b_1000_1010));
+    }
+
+    #[test]
+    fn generate() {
+        let game = create_small_game();
+        let tablebase = Tablebase::generate(game, 4, 2);
+        let mut games = 0;
+
+        assert!(!tablebase.is_empty());
+        assert_eq!(
+            Some(solver::solve(game, &mut games)),
+            tablebase.probe(&game)
+        );
+
+        // Rotations and reflections of reachable positions are reachable from
+        // an empty board.
+        let game = game.with_move(Puller, 1).with_move(Diagonal, 6);
+        assert_eq!(
+            Some(solver::solve(game, &mut games)),
+            tablebase.probe(&game.transform(3))
+        );
+
+        // Positions with more tiles remaining are not stored.
+        assert_eq!(None, tablebase.probe(&Game::default()));
+    }
+
+    #[test]
+    fn generate_max_tiles() {
+        let game = create_game(Puller, Pusher, Straight);
+        let tablebase = Tablebase::generate(game, 2, 1);
+
+        assert_eq!(2, tablebase.max_tiles());
+        assert_eq!(None, tablebase.probe(&game));
+
+        let game = game.with_move(Diagonal, 10).with_move(Diagonal, 15);
+        assert_eq!(Some(solver::solve(game, &mut 0)), tablebase.probe(&game));
+        assert_eq!(
+            Some(solver::solve(game.with_pass(), &mut 0)),
+            tablebase.probe(&game.with_pass())
+        );
+        assert_eq!(None, tablebase.verify(&mut 0));
+    }
+
+    #[test]
+    fn write_and_read() {
+        let tablebase = Tablebase::generate(create_game(Puller, Pusher, Straight), 2, 1);
+
+        let mut bytes = Vec::new();
+        tablebase.write(&mut bytes).unwrap();
+        assert_eq!(
+            Header::<4>::BYTES + tablebase.statuses.len() * 8,
+            bytes.len()
+        );
+        assert_eq!(tablebase, Tablebase::read(bytes.as_slice()).unwrap());
+
+        let mut truncated = bytes.clone();
+        truncated.pop();
+        assert!(matches!(
+            Tablebase::read(truncated.as_slice()),
+            Err(FileError::InvalidFormat(_))
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[Header::<4>::BYTES] ^= 1;
+        assert!(matches!(
+            Tablebase::read(corrupted.as_slice()),
+            Err(FileError::ChecksumMismatch)
+        ));
+
+        // An invalid status with a matching checksum.
+        let mut invalid_status = tablebase.clone();
+        invalid_status.statuses[0] |= 0b11;
+        let mut invalid_status_bytes = Vec::new();
+        invalid_status.write(&mut invalid_status_bytes).unwrap();
+        assert!(matches!(
+            Tablebase::read(invalid_status_bytes.as_slice()),
+            Err(FileError::InvalidFormat(_))
+        ));
+
+        // A starting position whose hands hold too many tiles is rejected
+        // before the tablebase is laid out.
+        let mut invalid_key = bytes.clone();
+        invalid_key[8 + 8 + 6..8 + 8 + 8].fill(0xff);
+        assert!(matches!(
+            Tablebase::read(invalid_key.as_slice()),
+            Err(FileError::InvalidFormat(_))
+        ));
+
+        let mut other_rules = bytes.clone();
+        other_rules[8] ^= 1;
+        assert!(matches!(
+            Tablebase::read(other_rules.as_slice()),
+            Err(FileError::RulesMismatch)
+        ));
+
+        bytes[0] = b'X';
+        assert_eq!(
+            "invalid format: not a tablebase file",
+            Tablebase::read(bytes.as_slice()).unwrap_err().to_string()
+        );
+    }
+}
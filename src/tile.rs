@@ -1,9 +1,11 @@
 //! Defines an enum representing a tile.
 
+use std::fmt::{self, Display};
+
 use crate::board::Board;
 
 /// Represents a tile.
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Tile {
     Puller,
     Pusher,
@@ -11,11 +13,114 @@ pub enum Tile {
     Diagonal,
 }
 
+impl Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tile::Puller => "Puller",
+            Tile::Pusher => "Pusher",
+            Tile::Straight => "Straight",
+            Tile::Diagonal => "Diagonal",
+        })
+    }
+}
+
 impl Tile {
+    /// All tiles, in order of discriminant.
+    pub const ALL: [Tile; 4] = [Tile::Puller, Tile::Pusher, Tile::Straight, Tile::Diagonal];
+
     /// The bit fields representing the squares unavailable after a move,
     /// indexed by tile discriminant and the square in which the tile was
     /// played.
-    const MOVES: [[u16; 16]; 4] = [
+    const MOVES: [[u16; 16]; 4] = Self::calculate_moves();
+
+    /// Returns the bit fields representing the squares unavailable after every
+    /// move.
+    ///
+    /// Only used in evaluating the MOVES constant at compile time.
+    const fn calculate_moves() -> [[u16; 16]; 4] {
+        let mut moves = [[0; 16]; 4];
+
+        let mut tile = 0;
+
+        while tile < 4 {
+            let mut square = 0;
+
+            while square < 16 {
+                moves[tile][square] = Self::ALL[tile].calculate_unavailable(square as u8);
+                square += 1;
+            }
+
+            tile += 1;
+        }
+
+        moves
+    }
+
+    /// Returns the bit field representing the squares unavailable after playing
+    /// the tile in the specified square, ignoring occupied squares.
+    ///
+    /// Only used in evaluating the MOVES constant at compile time.
+    const fn calculate_unavailable(&self, square: u8) -> u16 {
+        let row = (square / 4) as i16;
+        let column = (square % 4) as i16;
+
+        let mut available = 0;
+        let mut other = 0;
+
+        while other < 16 {
+            if self.allows((other / 4) as i16 - row, (other % 4) as i16 - column) {
+                available |= 1 << other;
+            }
+
+            other += 1;
+        }
+
+        !available
+    }
+
+    /// Returns whether playing the tile allows the next move in a square the
+    /// specified number of rows and columns away, ignoring whether the square
+    /// is occupied:
+    ///
+    /// - a Puller allows moves in the adjacent squares
+    /// - a Pusher allows moves in the squares that are not adjacent
+    /// - a Straight allows moves in the same row or column
+    /// - a Diagonal allows moves in the same diagonals
+    ///
+    /// A tile never allows a move in its own square.
+    pub(crate) const fn allows(&self, row_distance: i16, column_distance: i16) -> bool {
+        let row_distance = row_distance.abs();
+        let column_distance = column_distance.abs();
+        let distance = if row_distance > column_distance {
+            row_distance
+        } else {
+            column_distance
+        };
+
+        distance > 0
+            && match self {
+                Tile::Puller => distance == 1,
+                Tile::Pusher => distance > 1,
+                Tile::Straight => row_distance == 0 || column_distance == 0,
+                Tile::Diagonal => row_distance == column_distance,
+            }
+    }
+
+    /// Returns the board of squares unavailable after playing the tile in the
+    /// specified square.
+    pub fn get_unavailable(&self, square: u8) -> Board {
+        Board::from(Tile::MOVES[*self as usize][square as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tile::{self, *};
+    use crate::board::Board;
+
+    // The bit fields typed out by hand before `Tile::MOVES` was calculated from
+    // the rules for each tile.
+    const EXPECTED_MOVES: [[u16; 16]; 4] = [
         [
             0b_1111_1111_1100_1101,
             0b_1111_1111_1000_1010,
@@ -90,19 +195,62 @@ impl Tile {
         ],
     ];
 
-    /// Returns the board of squares unavailable after playing the tile in the
-    /// specified square.
-    pub fn get_unavailable(&self, square: u8) -> Board {
-        Board::from(Tile::MOVES[*self as usize][square as usize])
+    #[test]
+    fn fmt() {
+        assert_eq!("Puller", format!("{}", Puller));
+        assert_eq!("Diagonal", format!("{}", Diagonal));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::Tile::*;
 
     #[test]
     fn get_unavailable() {
         assert_eq!(0b_1111_1111_1100_1101, u16::from(Puller.get_unavailable(0)));
+        assert_eq!(0b_0000_0000_0011_0011, u16::from(Pusher.get_unavailable(0)));
+        assert_eq!(
+            0b_1110_1110_1110_0001,
+            u16::from(Straight.get_unavailable(0))
+        );
+        assert_eq!(
+            0b_0111_1011_1101_1111,
+            u16::from(Diagonal.get_unavailable(0))
+        );
+    }
+
+    #[test]
+    fn allows() {
+        assert!(Puller.allows(1, -1));
+        assert!(!Puller.allows(0, 2));
+        assert!(Pusher.allows(0, -2));
+        assert!(!Pusher.allows(1, 1));
+        assert!(Straight.allows(-3, 0));
+        assert!(!Straight.allows(1, 2));
+        assert!(Diagonal.allows(-2, 2));
+        assert!(!Diagonal.allows(0, 1));
+
+        for tile in Tile::ALL {
+            assert!(!tile.allows(0, 0));
+        }
+    }
+
+    #[test]
+    fn calculate_moves() {
+        assert_eq!(EXPECTED_MOVES, Tile::MOVES);
+    }
+
+    #[test]
+    fn get_unavailable_symmetric() {
+        // Transforming the squares made unavailable by a tile must give the
+        // squares made unavailable by the tile in the transformed square.
+        for tile in Tile::ALL {
+            for square in 0..16 {
+                for symmetry in 0..8 {
+                    let transformed = Board::default().with(square).transform(symmetry);
+
+                    assert_eq!(
+                        tile.get_unavailable(square).transform(symmetry),
+                        tile.get_unavailable(u16::from(transformed).trailing_zeros() as u8)
+                    );
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,588 @@
+//! Defines structs representing boards of up to 64 squares, their shapes, and
+//! the game state on them.
+
+use std::fmt::{self, Display};
+
+use crate::hand::Hand;
+use crate::status::Status;
+use crate::tile::Tile;
+
+/// The number of columns, and the maximum number of rows, of the grid of
+/// squares represented by `WideBoard`.
+pub const WIDTH: u8 = 8;
+
+/// Represents a board of up to 64 squares.
+///
+/// The board is represented as a `u64` bit field with the squares of an 8×8
+/// grid corresponding to these bits:
+///
+/// ```text
+///  0  1  2  3  4  5  6  7
+///  8  9 10 11 12 13 14 15
+/// ...
+/// 56 57 58 59 60 61 62 63
+/// ```
+///
+/// The `From` trait is implemented to allow conversion to and from a `u64`.
+///
+/// Unlike `Board`, which is limited to 16 squares so that scores can be looked
+/// up in a table, boards of other shapes (such as 5×5 or 6×6 boards) are placed
+/// within the grid, and scored by a `WideGeometry`. The standard board should
+/// still use `Board`, which is much faster.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct WideBoard(u64);
+
+impl From<WideBoard> for u64 {
+    fn from(value: WideBoard) -> u64 {
+        value.0
+    }
+}
+
+impl From<u64> for WideBoard {
+    fn from(value: u64) -> WideBoard {
+        WideBoard(value)
+    }
+}
+
+impl Display for WideBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+
+        for square in 0..64 {
+            if square > 0 {
+                s.push(if square % WIDTH == 0 { '\n' } else { ' ' });
+            }
+
+            s.push(if self.is_available(square) {
+                '□'
+            } else {
+                '■'
+            });
+        }
+
+        f.write_str(&s)
+    }
+}
+
+impl WideBoard {
+    /// Returns whether the specified square is available.
+    pub fn is_available(&self, square: u8) -> bool {
+        self.0 & (1 << square) == 0
+    }
+
+    /// Returns an instance with the specified square marked as occupied.
+    pub fn with(&self, square: u8) -> WideBoard {
+        WideBoard(self.0 | (1 << square))
+    }
+
+    /// Returns the number of occupied squares.
+    pub fn count(&self) -> u8 {
+        self.0.count_ones() as u8
+    }
+
+    /// Returns an instance with each square marked as occupied if it is
+    /// occupied in either of the specified boards.
+    pub fn merge(board_1: WideBoard, board_2: WideBoard) -> WideBoard {
+        WideBoard(board_1.0 | board_2.0)
+    }
+}
+
+/// Describes the shape of a board within the 8×8 grid of squares represented
+/// by `WideBoard`, and the lines of squares that score.
+///
+/// Every horizontal, vertical, and diagonal line of the specified number of
+/// consecutive playable squares scores. Rather than storing each line, the
+/// geometry stores, for each direction, the squares at which a line starts,
+/// so a board is scored by shifting it along each direction once per square
+/// in a line and counting the bits that remain set with a popcount.
+///
+/// The game is over when the player to move has no tiles left, or when every
+/// playable square is occupied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WideGeometry {
+    /// The board of playable squares.
+    playable: WideBoard,
+
+    /// The number of squares in the lines that score.
+    line_length: u8,
+
+    /// The shift between consecutive squares of a line in each direction, and
+    /// the bit field of squares at which a line of playable squares starts.
+    lines: [(u8, u64); 4],
+
+    /// The squares unavailable for the first move, in addition to those that
+    /// are not playable.
+    start_unavailable: WideBoard,
+
+    /// The bit fields representing the squares unavailable after a move,
+    /// indexed by tile discriminant and the square in which the tile was
+    /// played, including the squares that are not playable.
+    moves: [[u64; 64]; 4],
+}
+
+impl WideGeometry {
+    /// Returns the geometry with the specified playable squares, scoring every
+    /// horizontal, vertical, and diagonal line of the specified number of
+    /// consecutive playable squares.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line length is not between 2 and 8. A line of one square
+    /// would be counted once in each direction.
+    pub fn new(playable: WideBoard, line_length: u8) -> Self {
+        assert!(
+            (2..=WIDTH).contains(&line_length),
+            "lines have between 2 and 8 squares"
+        );
+
+        let is_playable = |row: i8, column: i8| {
+            (0..WIDTH as i8).contains(&row)
+                && (0..WIDTH as i8).contains(&column)
+                && !playable.is_available(row as u8 * WIDTH + column as u8)
+        };
+
+        let lines = [(0, 1), (1, 0), (1, 1), (1, -1)].map(|(row_step, column_step)| {
+            let mut starts = 0;
+
+            for square in 0..64 {
+                let (row, column) = ((square / WIDTH) as i8, (square % WIDTH) as i8);
+
+                if (0..line_length as i8)
+                    .all(|index| is_playable(row + row_step * index, column + column_step * index))
+                {
+                    starts |= 1 << square;
+                }
+            }
+
+            ((row_step * WIDTH as i8 + column_step) as u8, starts)
+        });
+
+        let mut moves = [[u64::MAX; 64]; 4];
+
+        for tile in Tile::ALL {
+            for square in 0..64 {
+                if playable.is_available(square) {
+                    continue;
+                }
+
+                for other in 0..64 {
+                    if !playable.is_available(other)
+                        && tile.allows(
+                            (other / WIDTH) as i16 - (square / WIDTH) as i16,
+                            (other % WIDTH) as i16 - (square % WIDTH) as i16,
+                        )
+                    {
+                        moves[tile as usize][square as usize] &= !(1 << other);
+                    }
+                }
+            }
+        }
+
+        WideGeometry {
+            playable,
+            line_length,
+            lines,
+            start_unavailable: WideBoard::default(),
+            moves,
+        }
+    }
+
+    /// Returns the geometry of a rectangular board with the specified number of
+    /// rows and columns in the top left corner of the grid, scoring every line
+    /// of the specified number of consecutive squares.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board does not fit within the grid, or if the line length
+    /// is not between 2 and 8.
+    pub fn rectangle(rows: u8, columns: u8, line_length: u8) -> Self {
+        assert!(
+            rows <= WIDTH && columns <= WIDTH,
+            "a board has at most 8 rows and 8 columns"
+        );
+
+        let playable = (0..rows * columns).fold(WideBoard::default(), |playable, index| {
+            playable.with(index / columns * WIDTH + index % columns)
+        });
+
+        WideGeometry::new(playable, line_length)
+    }
+
+    /// Returns the geometry with playable squares at the specified rows and
+    /// columns, such as the squares of an `Arrangement`, scoring every line of
+    /// the specified number of consecutive squares.
+    ///
+    /// The squares are moved so that the topmost and leftmost squares are in
+    /// the top row and left column of the grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the squares do not fit within the grid, or if the line length
+    /// is not between 2 and 8.
+    pub fn from_squares(squares: &[(i8, i8)], line_length: u8) -> Self {
+        let top = squares.iter().map(|&(row, _)| row).min().unwrap_or(0);
+        let left = squares.iter().map(|&(_, column)| column).min().unwrap_or(0);
+
+        let playable = squares
+            .iter()
+            .fold(WideBoard::default(), |playable, &(row, column)| {
+                let (row, column) = (row as i16 - top as i16, column as i16 - left as i16);
+
+                assert!(
+                    row < WIDTH as i16 && column < WIDTH as i16,
+                    "the squares must fit within 8 rows and 8 columns"
+                );
+
+                playable.with(row as u8 * WIDTH + column as u8)
+            });
+
+        WideGeometry::new(playable, line_length)
+    }
+
+    /// Returns the geometry with the specified squares unavailable for the
+    /// first move.
+    pub fn with_start_unavailable(self, start_unavailable: WideBoard) -> Self {
+        WideGeometry {
+            start_unavailable,
+            ..self
+        }
+    }
+
+    /// Returns the board of playable squares.
+    pub fn get_playable(&self) -> WideBoard {
+        self.playable
+    }
+
+    /// Returns the lines of squares that score.
+    pub fn get_lines(&self) -> Vec<WideBoard> {
+        let mut lines = Vec::new();
+
+        for (shift, starts) in self.lines {
+            for square in 0..64 {
+                if starts & 1 << square != 0 {
+                    lines.push(WideBoard(
+                        (0..self.line_length)
+                            .fold(0, |line, index| line | 1 << (square + shift * index)),
+                    ));
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Returns the initial game state, with no squares being occupied, each
+    /// player having a complete hand, and every playable square allowed for the
+    /// first move being available.
+    pub fn initial_game(&self) -> WideGame {
+        WideGame::new(
+            WideBoard::default(),
+            Default::default(),
+            WideBoard::default(),
+            Default::default(),
+            WideBoard::merge(self.start_unavailable, self.unplayable()),
+        )
+    }
+
+    /// Returns the score for the specified board.
+    pub fn get_score(&self, board: WideBoard) -> u16 {
+        self.lines
+            .iter()
+            .map(|&(shift, starts)| {
+                (0..self.line_length)
+                    .fold(starts, |line, index| line & board.0 >> (shift * index))
+                    .count_ones()
+            })
+            .sum::<u32>() as u16
+    }
+
+    /// Returns the board of squares unavailable after playing the specified
+    /// tile in the specified square.
+    pub fn get_unavailable(&self, tile: Tile, square: u8) -> WideBoard {
+        WideBoard(self.moves[tile as usize][square as usize])
+    }
+
+    /// Returns whether the game is over, because the current player's hand is
+    /// empty or every playable square is occupied.
+    pub fn is_over(&self, game: &WideGame) -> bool {
+        game.hand.is_empty()
+            || WideBoard::merge(
+                WideBoard::merge(game.board, game.opponent_board),
+                self.unplayable(),
+            )
+            .0 == u64::MAX
+    }
+
+    /// Returns the victory status of the specified game state.
+    pub fn get_status(&self, game: &WideGame) -> Status {
+        match self.get_margin(game) {
+            margin if margin > 0 => Status::Win,
+            0 => Status::Draw,
+            _ => Status::Loss,
+        }
+    }
+
+    /// Returns the margin by which the current player's score exceeds the
+    /// opposing player's score, which is negative if the current player is
+    /// behind.
+    pub fn get_margin(&self, game: &WideGame) -> i16 {
+        self.get_score(game.board) as i16 - self.get_score(game.opponent_board) as i16
+    }
+
+    /// Returns an instance for the opponent after the current player has
+    /// passed, allowing the opponent to play in any unoccupied playable
+    /// square.
+    pub fn with_pass(&self, game: &WideGame) -> WideGame {
+        WideGame::new(
+            game.opponent_board,
+            game.opponent_hand,
+            game.board,
+            game.hand,
+            self.unplayable(),
+        )
+    }
+
+    /// Returns an instance for the opponent after the specified move.
+    pub fn with_move(&self, game: &WideGame, tile: Tile, square: u8) -> WideGame {
+        WideGame::new(
+            game.opponent_board,
+            game.opponent_hand,
+            game.board.with(square),
+            game.hand.without(tile),
+            self.get_unavailable(tile, square),
+        )
+    }
+
+    /// Returns the board of squares that are not playable.
+    fn unplayable(&self) -> WideBoard {
+        WideBoard(!self.playable.0)
+    }
+}
+
+/// Represents the game state on a board with a `WideGeometry`.
+///
+/// As for `Game`, only the state necessary to solve the game is stored, and
+/// the rules that depend on the shape of the board are implemented by the
+/// geometry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WideGame {
+    /// The board of squares occupied by the current player.
+    board: WideBoard,
+
+    /// The current player's hand.
+    hand: Hand,
+
+    /// The board of squares occupied by the opposing player.
+    opponent_board: WideBoard,
+
+    /// The opposing player's hand.
+    opponent_hand: Hand,
+
+    /// The board of squares unavailable due to the previous player's move, or
+    /// because they are not playable.
+    unavailable: WideBoard,
+}
+
+impl WideGame {
+    /// Returns an instance with the specified state.
+    ///
+    /// Occupied squares are always unavailable, so are added to the board of
+    /// unavailable squares if they are not already included.
+    pub fn new(
+        board: WideBoard,
+        hand: Hand,
+        opponent_board: WideBoard,
+        opponent_hand: Hand,
+        unavailable: WideBoard,
+    ) -> Self {
+        WideGame {
+            board,
+            hand,
+            opponent_board,
+            opponent_hand,
+            unavailable: WideBoard::merge(WideBoard::merge(board, opponent_board), unavailable),
+        }
+    }
+
+    /// Returns an instance with the specified hands for the current player and
+    /// the opposing player.
+    pub fn with_hands(&self, hand: Hand, opponent_hand: Hand) -> Self {
+        WideGame {
+            hand,
+            opponent_hand,
+            ..*self
+        }
+    }
+
+    /// Returns the board of squares occupied by the current player.
+    pub fn get_board(&self) -> WideBoard {
+        self.board
+    }
+
+    /// Returns the current player's hand.
+    pub fn get_hand(&self) -> Hand {
+        self.hand
+    }
+
+    /// Returns the board of squares occupied by the opposing player.
+    pub fn get_opponent_board(&self) -> WideBoard {
+        self.opponent_board
+    }
+
+    /// Returns the opposing player's hand.
+    pub fn get_opponent_hand(&self) -> Hand {
+        self.opponent_hand
+    }
+
+    /// Returns the board of squares unavailable to the current player.
+    pub fn get_unavailable(&self) -> WideBoard {
+        self.unavailable
+    }
+
+    /// Returns the number of tiles remaining in both players' hands.
+    pub fn tiles_remaining(&self) -> u8 {
+        self.hand.len() + self.opponent_hand.len()
+    }
+
+    /// Returns whether the player must pass (because all squares are
+    /// unavailable).
+    pub fn player_must_pass(&self) -> bool {
+        self.unavailable.0 == u64::MAX
+    }
+
+    /// Returns whether the specified square is available.
+    pub fn is_available(&self, square: u8) -> bool {
+        self.unavailable.is_available(square)
+    }
+
+    /// Returns whether the current player's hand contains the specified tile.
+    pub fn has(&self, tile: Tile) -> bool {
+        self.hand.has(tile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::geometry::BoardGeometry;
+    use crate::tile::Tile::*;
+
+    #[test]
+    fn count() {
+        assert_eq!(0, WideBoard::default().count());
+        assert_eq!(2, WideBoard::default().with(0).with(63).count());
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(24, WideGeometry::rectangle(4, 4, 3).get_lines().len());
+
+        // A 5×5 board has three lines of three squares in each row and column,
+        // and nine in each diagonal direction.
+        assert_eq!(48, WideGeometry::rectangle(5, 5, 3).get_lines().len());
+        assert_eq!(12, WideGeometry::rectangle(5, 5, 5).get_lines().len());
+        assert_eq!(54, WideGeometry::rectangle(6, 6, 4).get_lines().len());
+
+        // Lines do not wrap from one row to the next.
+        assert_eq!(12, WideGeometry::rectangle(2, 8, 3).get_lines().len());
+    }
+
+    #[test]
+    fn from_squares() {
+        assert_eq!(
+            WideGeometry::rectangle(2, 3, 2),
+            WideGeometry::from_squares(&[(-1, 2), (-1, 3), (-1, 4), (0, 2), (0, 3), (0, 4)], 2)
+        );
+    }
+
+    #[test]
+    fn get_score() {
+        // Scores on the 4×4 board in the top left corner match the scores on
+        // the standard board, with each row of four squares moved into a row
+        // of eight squares.
+        let geometry = WideGeometry::rectangle(4, 4, 3);
+
+        for board in (0..=u16::MAX).step_by(7) {
+            let wide = (0..16).fold(WideBoard::default(), |wide, square| {
+                if Board::from(board).is_available(square) {
+                    wide
+                } else {
+                    wide.with(square / 4 * WIDTH + square % 4)
+                }
+            });
+
+            assert_eq!(
+                u16::from(Board::from(board).get_score()),
+                geometry.get_score(wide)
+            );
+        }
+
+        // A full board scores every line, which on an 8×8 board with lines of
+        // two squares is more than fits in a byte.
+        for (size, line_length) in [(6, 3), (8, 2)] {
+            let geometry = WideGeometry::rectangle(size, size, line_length);
+            assert_eq!(
+                geometry.get_lines().len() as u16,
+                geometry.get_score(geometry.get_playable())
+            );
+        }
+
+        let geometry = WideGeometry::rectangle(8, 8, 2);
+        let game = WideGame::new(
+            geometry.get_playable(),
+            Hand::default(),
+            WideBoard::default(),
+            Hand::default(),
+            geometry.get_playable(),
+        );
+        assert_eq!(210, geometry.get_margin(&game));
+        assert_eq!(-210, geometry.get_margin(&geometry.with_pass(&game)));
+    }
+
+    #[test]
+    #[should_panic(expected = "lines have between 2 and 8 squares")]
+    fn new_with_short_lines() {
+        WideGeometry::rectangle(8, 8, 1);
+    }
+
+    #[test]
+    fn get_unavailable() {
+        let standard = BoardGeometry::default();
+        let geometry = WideGeometry::rectangle(4, 4, 3);
+
+        for tile in Tile::ALL {
+            for square in 0..16 {
+                let unavailable = geometry.get_unavailable(tile, square / 4 * WIDTH + square % 4);
+
+                for other in 0..16 {
+                    assert_eq!(
+                        standard.get_unavailable(tile, square).is_available(other),
+                        unavailable.is_available(other / 4 * WIDTH + other % 4)
+                    );
+                }
+            }
+        }
+
+        // Squares that are not playable are always unavailable.
+        assert!(!geometry.get_unavailable(Pusher, 0).is_available(4));
+    }
+
+    #[test]
+    fn is_over() {
+        let geometry = WideGeometry::rectangle(1, 2, 2);
+        let game = geometry.initial_game();
+        assert!(!geometry.is_over(&game));
+
+        // A Pusher makes the adjacent square unavailable, so the opposing
+        // player must pass, after which every playable square is occupied.
+        let game = geometry.with_move(&game, Pusher, 0);
+        assert!(game.player_must_pass());
+        assert!(!geometry.is_over(&game));
+
+        let game = geometry.with_move(&geometry.with_pass(&game), Puller, 1);
+        assert!(geometry.is_over(&game));
+        assert_eq!(-1, geometry.get_margin(&game));
+        assert_eq!(Status::Loss, geometry.get_status(&game));
+    }
+}